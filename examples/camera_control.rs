@@ -24,6 +24,7 @@ fn main() {
             // Using LowPass filter, with value of 'alpha' at 0.01 for last 20 frames to get more smoothing
             config_filter_length: 20,
             config_filter_type: SmoothingFilterType::LowPass(0.01),
+            ..default()
         })
         // Using HookPlugin to get named object from loaded gltf scene
         .add_plugins(HookPlugin)
@@ -31,7 +32,7 @@ fn main() {
         .add_systems(
             Update,
             (
-                set_camera_position_from_plugin,
+                set_camera_position_from_plugin.after(WebcamFacialSet::Receive),
                 user_input_to_plugin_control_system,
             ),
         )
@@ -5,7 +5,6 @@ use bevy_webcam_facial::*;
 
 fn main() {
     App::new()
-        .insert_resource(Average::default())
         .insert_resource(ClearColor(Color::WHITE))
         .insert_resource(AmbientLight {
             color: Color::WHITE,
@@ -18,27 +17,34 @@ fn main() {
             config_webcam_height: 480,
             config_webcam_framerate: 33,
             config_webcam_autostart: true,
+            config_detector_type: detector_type(),
             config_filter_length: 15,
             config_filter_type: SmoothingFilterType::LowPass(0.1),
+            ..default()
         })
         .add_plugins(HookPlugin)
         .add_systems(Startup, load_scene)
-        .add_systems(Update, (keyboard_animation_control, bone_move))
+        .add_systems(Update, keyboard_animation_control)
         .run();
 }
 
-#[derive(Resource, Default)]
-struct Average {
-    x: f32,
-    y: f32,
+// Run with `--features onnx` to use UltraFace model, it keeps track of turned head much better
+#[cfg(feature = "onnx")]
+fn detector_type() -> FaceDetectorType {
+    FaceDetectorType::UltraFace {
+        model_path: "assets/NN_Models/version-RFB-320.onnx".to_string(),
+        score_threshold: 0.7,
+    }
+}
+
+#[cfg(not(feature = "onnx"))]
+fn detector_type() -> FaceDetectorType {
+    FaceDetectorType::default()
 }
 
 #[derive(Debug)]
 struct Animations(Vec<Handle<AnimationClip>>);
 
-#[derive(Component)]
-struct HeadBone;
-
 /// set up 3D scene
 fn load_scene(mut cmds: Commands, asset_server: Res<AssetServer>) {
     cmds.spawn(HookedSceneBundle {
@@ -48,7 +54,12 @@ fn load_scene(mut cmds: Commands, asset_server: Res<AssetServer>) {
         },
         hook: SceneHook::new(|entity, cmds| {
             match entity.get::<Name>().map(|t| t.as_str()) {
-                Some("Headas") => cmds.insert(HeadBone),
+                // Head bone follows player's head turns
+                Some("Headas") => cmds.insert(
+                    FaceDriven::default()
+                        .map(FaceSource::CenterX, FaceTarget::RotationYaw, 0.8)
+                        .map(FaceSource::CenterY, FaceTarget::RotationPitch, 0.8),
+                ),
                 _ => cmds,
             };
         }),
@@ -71,18 +82,3 @@ fn keyboard_animation_control(
         if keyboard_input.just_pressed(KeyCode::Up) {}
     }
 }
-fn bone_move(
-    mut head: Query<&mut Transform, With<HeadBone>>,
-    mut average: ResMut<Average>,
-    mut reader: EventReader<WebcamFacialDataEvent>,
-) {
-    for event in reader.iter() {
-        let x = event.0.center_x as f32;
-        let y = event.0.center_y as f32;
-        average.x = (x + average.x) / 2.0;
-        average.y = (y + average.y) / 2.0;
-        for mut head in head.iter_mut() {
-            head.translation += 0.005;
-        }
-    }
-}
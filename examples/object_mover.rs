@@ -3,9 +3,6 @@ use bevy::prelude::*;
 
 use bevy_webcam_facial::*;
 
-#[derive(Component)]
-struct WebcamControlledObject;
-
 fn main() {
     App::new()
         .insert_resource(DirectionalLightShadowMap { size: 2048 })
@@ -13,8 +10,6 @@ fn main() {
         // Add plugin with a *MUST* camera parameters
         .add_plugins(WebcamFacialPlugin::default())
         .add_systems(Startup, setup)
-        // Add system to read data events and do something with data
-        .add_systems(Update, move_object)
         .run();
 }
 
@@ -30,16 +25,18 @@ fn setup(
         material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
         ..default()
     });
-    // Cube object with WebcamControlledObject
+    // Cube object driven by face data
     commands.spawn((
         PbrBundle {
             mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
             material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
-            transform: Transform::from_xyz(0.0, 1.0, 0.0),
+            transform: Transform::from_xyz(0.0, 1.0, 2.0),
             ..default()
         },
-        // Mark object
-        WebcamControlledObject,
+        // Move object with x10 less influence from starting position
+        FaceDriven::default()
+            .map(FaceSource::CenterX, FaceTarget::TranslationX, 0.1)
+            .map(FaceSource::CenterY, FaceTarget::TranslationZ, 0.1),
     ));
     // Some light
     commands.spawn(PointLightBundle {
@@ -57,23 +54,3 @@ fn setup(
         ..default()
     });
 }
-
-fn move_object(
-    // Get object to control with WebcamControlledObject
-    mut query: Query<&mut Transform, With<WebcamControlledObject>>,
-    // Read data event with camera data
-    mut reader: EventReader<WebcamFacialDataEvent>,
-) {
-    for event in reader.iter() {
-        // Get data from event.
-        let x = event.0.center_x;
-        let y = event.0.center_y;
-        // Print coords and do basic transforms
-        info!("{:?} {:?}", x, y);
-        for mut transform in query.iter_mut() {
-            // Move object with x10 less influence, also add 2 to z transform
-            transform.translation.x = x as f32 / 10.0;
-            transform.translation.z = y as f32 / 10.0 + 2.0;
-        }
-    }
-}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use bevy_webcam_facial::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(WebcamFacialPlugin::default())
+        // Head-coupled camera projection
+        .add_plugins(HeadCoupledCameraPlugin)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+/// set up a scene behind the screen "window"
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Row of cubes going away from window to see parallax
+    for i in 0..5 {
+        commands.spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 0.5 })),
+            material: materials.add(Color::rgb(0.8, 0.7 - i as f32 * 0.1, 0.6).into()),
+            transform: Transform::from_xyz(i as f32 * 0.4 - 0.8, 0.0, -(i as f32) * 1.5 - 1.0),
+            ..default()
+        });
+    }
+    // Some light
+    commands.spawn(PointLightBundle {
+        point_light: PointLight {
+            intensity: 500.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform::from_xyz(0.0, 4.0, 0.0),
+        ..default()
+    });
+    // Camera looking through 2 units wide window at scene origin
+    commands.spawn((
+        Camera3dBundle::default(),
+        HeadCoupledProjection {
+            window: Transform::from_xyz(0.0, 0.0, 0.0),
+            window_width: 2.0,
+            head_range: Vec2::new(0.6, 0.4),
+            eye_distance: 2.0,
+            ..default()
+        },
+    ));
+}
@@ -0,0 +1,85 @@
+// Automatic start/stop of capture from app States and window focus
+use bevy::{
+    app::{App, Plugin, PostStartup},
+    ecs::{
+        query::With,
+        schedule::{OnEnter, OnExit, States},
+        system::{Query, ResMut},
+    },
+    window::{PrimaryWindow, Window},
+};
+
+use crate::{WebcamFacialCamera, WebcamFacialController};
+
+#[derive(Clone, Copy, Debug)]
+pub enum WindowFocusPolicy {
+    // Capture continues when window is not focused
+    Ignore,
+    // Capture is paused while primary window is not focused and resumed on focus
+    PauseUnfocused,
+}
+
+// Add to plugins together with WebcamFacialPlugin to capture only in given states.
+// Capture of primary camera and all WebcamFacialCamera entities is stopped at startup
+// and started/stopped on entering/exiting listed states.
+pub struct WebcamFacialStatesPlugin<S: States> {
+    pub states: Vec<S>,
+}
+
+impl<S: States> WebcamFacialStatesPlugin<S> {
+    pub fn new(states: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            states: states.into_iter().collect(),
+        }
+    }
+}
+
+impl<S: States> Plugin for WebcamFacialStatesPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, webcam_facial_stop);
+        for state in self.states.iter() {
+            // Exit runs before enter, so moving between listed states keeps capture running
+            app.add_systems(OnEnter(state.clone()), webcam_facial_start)
+                .add_systems(OnExit(state.clone()), webcam_facial_stop);
+        }
+    }
+}
+
+fn webcam_facial_start(
+    webcam_facial: Option<ResMut<WebcamFacialController>>,
+    mut cameras: Query<&mut WebcamFacialCamera>,
+) {
+    if let Some(mut webcam_facial) = webcam_facial {
+        webcam_facial.control = true;
+    }
+    for mut camera in cameras.iter_mut() {
+        camera.control = true;
+    }
+}
+
+fn webcam_facial_stop(
+    webcam_facial: Option<ResMut<WebcamFacialController>>,
+    mut cameras: Query<&mut WebcamFacialCamera>,
+) {
+    if let Some(mut webcam_facial) = webcam_facial {
+        webcam_facial.control = false;
+    }
+    for mut camera in cameras.iter_mut() {
+        camera.control = false;
+    }
+}
+
+pub(crate) fn window_focus_system(
+    mut webcam_facial: ResMut<WebcamFacialController>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    // Without window (headless app) capture is never paused
+    let focused = windows.get_single().map_or(true, |window| window.focused);
+    let paused = match webcam_facial.config_focus_policy {
+        WindowFocusPolicy::Ignore => false,
+        WindowFocusPolicy::PauseUnfocused => !focused,
+    };
+    if webcam_facial.paused != paused {
+        webcam_facial.paused = paused;
+    }
+}
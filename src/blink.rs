@@ -0,0 +1,128 @@
+// Eye openness from landmarks with blink and wink events
+use bevy::ecs::{
+    change_detection::DetectChanges,
+    event::{Event, EventWriter},
+    system::{Local, Res, ResMut, Resource},
+};
+use std::time::Duration;
+
+use crate::{WebcamFacialController, WebcamFacialState};
+
+#[derive(Clone, Copy, Debug)]
+pub enum BlinkDetectionType {
+    // Eye is closed when its aspect ratio is below closed_ratio,
+    // blink or wink has to last at least min_duration seconds
+    Threshold {
+        closed_ratio: f32,
+        min_duration: f32,
+    },
+    NoBlinkDetection,
+}
+
+// Player's eye
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+// Latest eye openness of player, needs 68 point landmarks. Reset to default while face is lost
+#[derive(Resource, Default, Clone, Debug)]
+pub struct FaceEyes {
+    // Eye aspect ratio, about 0.3 when open and close to 0.0 when closed
+    pub left_openness: f32,
+    pub right_openness: f32,
+    pub left_closed: bool,
+    pub right_closed: bool,
+}
+
+// Sent when both eyes open again after blink
+#[derive(Event, Clone, Debug)]
+pub struct BlinkEvent {
+    pub duration: Duration,
+}
+
+// Sent when eye opens again after being closed alone
+#[derive(Event, Clone, Debug)]
+pub struct WinkEvent {
+    pub eye: Eye,
+    pub duration: Duration,
+}
+
+#[derive(Default)]
+pub(crate) struct BlinkTracker {
+    blink_since: Option<Duration>,
+    wink_since: Option<(Eye, Duration)>,
+}
+
+pub(crate) fn face_blink_system(
+    webcam_facial: Res<WebcamFacialController>,
+    facial_state: Res<WebcamFacialState>,
+    mut eyes: ResMut<FaceEyes>,
+    mut tracker: Local<BlinkTracker>,
+    mut blinks: EventWriter<BlinkEvent>,
+    mut winks: EventWriter<WinkEvent>,
+) {
+    if !facial_state.is_changed() {
+        return;
+    }
+    let (closed_ratio, min_duration) = match webcam_facial.config_blink_type {
+        BlinkDetectionType::Threshold {
+            closed_ratio,
+            min_duration,
+        } => (closed_ratio, Duration::from_secs_f32(min_duration)),
+        BlinkDetectionType::NoBlinkDetection => return,
+    };
+    // Player's left eye is on the right side of camera frame
+    let landmarks = &facial_state.landmarks;
+    let (left, right) = match (
+        landmarks.right_eye_aspect_ratio(),
+        landmarks.left_eye_aspect_ratio(),
+    ) {
+        (Some(left), Some(right)) if facial_state.present => (left, right),
+        // Lost face is not a blink, start over when it is back
+        _ => {
+            *tracker = BlinkTracker::default();
+            *eyes = FaceEyes::default();
+            return;
+        }
+    };
+    eyes.left_openness = left;
+    eyes.right_openness = right;
+    eyes.left_closed = left < closed_ratio;
+    eyes.right_closed = right < closed_ratio;
+
+    let now = facial_state.timestamp;
+    match (eyes.left_closed, eyes.right_closed) {
+        (true, true) => {
+            // Wink turned into blink
+            tracker.wink_since = None;
+            tracker.blink_since.get_or_insert(now);
+        }
+        (false, false) => {
+            if let Some(since) = tracker.blink_since.take() {
+                let duration = now.saturating_sub(since);
+                if duration >= min_duration {
+                    blinks.send(BlinkEvent { duration });
+                }
+            }
+            if let Some((eye, since)) = tracker.wink_since.take() {
+                let duration = now.saturating_sub(since);
+                if duration >= min_duration {
+                    winks.send(WinkEvent { eye, duration });
+                }
+            }
+        }
+        (left_closed, _) => {
+            // Eyes rarely open in the same frame, blink ends only when both are open
+            if tracker.blink_since.is_some() {
+                return;
+            }
+            let eye = if left_closed { Eye::Left } else { Eye::Right };
+            match tracker.wink_since {
+                Some((winking, _)) if winking == eye => {}
+                _ => tracker.wink_since = Some((eye, now)),
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+// Calibration of player's neutral head position and range of motion
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        event::{Event, EventReader, EventWriter},
+        system::{Local, Res, ResMut, Resource},
+    },
+    log::info,
+    time::Time,
+};
+use std::time::Duration;
+
+use crate::{
+    depth::frame_center_y, FaceDepth, WebcamFacialController, WebcamFacialData, WebcamFacialState,
+};
+
+// Calibrated ranges of face center coordinates (in -50.0 .. 50.0 units) for one player
+#[derive(Resource, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceCalibration {
+    pub neutral_x: f32,
+    pub neutral_y: f32,
+    pub left: f32,
+    pub right: f32,
+    pub up: f32,
+    pub down: f32,
+    // Player's face width in meters for distance estimation
+    pub face_width: Option<f32>,
+}
+
+// Uncalibrated defaults of square frame map full -50.0 .. 50.0 range, plugin inserts
+// uncalibrated ranges of its own frame size
+impl Default for FaceCalibration {
+    fn default() -> Self {
+        Self::uncalibrated(1.0)
+    }
+}
+
+impl FaceCalibration {
+    // Ranges of whole frame with aspect ratio of frame height to width. Neutral y is frame
+    // center, up and down are top and bottom edges of frame, as y is scaled by frame width.
+    pub fn uncalibrated(aspect_ratio: f32) -> Self {
+        let neutral_y = frame_center_y(aspect_ratio);
+        Self {
+            neutral_x: 0.0,
+            neutral_y,
+            left: -50.0,
+            right: 50.0,
+            up: neutral_y - 50.0 * aspect_ratio,
+            down: neutral_y + 50.0 * aspect_ratio,
+            face_width: None,
+        }
+    }
+
+    // Remaps face center x to range -1.0 (left extreme) .. 1.0 (right extreme)
+    pub fn remap_x(&self, center_x: f32) -> f32 {
+        remap(center_x, self.left, self.neutral_x, self.right)
+    }
+
+    // Remaps face center y to range -1.0 (up extreme) .. 1.0 (down extreme)
+    pub fn remap_y(&self, center_y: f32) -> f32 {
+        remap(center_y, self.up, self.neutral_y, self.down)
+    }
+}
+
+fn remap(value: f32, min: f32, neutral: f32, max: f32) -> f32 {
+    let range = if value >= neutral {
+        max - neutral
+    } else {
+        neutral - min
+    };
+    if range <= f32::EPSILON {
+        return 0.0;
+    }
+    ((value - neutral) / range).clamp(-1.0, 1.0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FaceCalibrationStep {
+    Neutral,
+    Left,
+    Right,
+    Up,
+    Down,
+    // Player sits at given distance from camera in meters
+    Distance(f32),
+}
+
+// Send to start recording one calibration step for given time
+#[derive(Event)]
+pub struct FaceCalibrationRequest {
+    pub step: FaceCalibrationStep,
+    pub duration: Duration,
+}
+
+// Sent when calibration step is recorded and stored in FaceCalibration resource
+#[derive(Event)]
+pub struct FaceCalibrationStepDone(pub FaceCalibrationStep);
+
+#[derive(Default)]
+pub(crate) struct FaceCalibrationRecording {
+    step: Option<FaceCalibrationStep>,
+    remaining: Duration,
+    samples: Vec<WebcamFacialData>,
+}
+
+pub(crate) fn face_calibration_system(
+    webcam_facial: Res<WebcamFacialController>,
+    time: Res<Time>,
+    facial_state: Res<WebcamFacialState>,
+    mut requests: EventReader<FaceCalibrationRequest>,
+    mut done: EventWriter<FaceCalibrationStepDone>,
+    mut recording: Local<FaceCalibrationRecording>,
+    mut calibration: ResMut<FaceCalibration>,
+) {
+    // New request replaces step being recorded
+    if let Some(request) = requests.iter().last() {
+        info!("Recording face calibration step {:?}.", request.step);
+        recording.step = Some(request.step);
+        recording.remaining = request.duration;
+        recording.samples.clear();
+    }
+    let step = match recording.step {
+        Some(step) => step,
+        None => return,
+    };
+    // Record only frames with face found
+    if facial_state.is_changed() && facial_state.present {
+        recording.samples.push(facial_state.filtered.clone());
+    }
+    recording.remaining = recording.remaining.saturating_sub(time.delta());
+    if !recording.remaining.is_zero() {
+        return;
+    }
+    recording.step = None;
+    if recording.samples.is_empty() {
+        info!("No face found while recording calibration step {:?}.", step);
+        return;
+    }
+    let samples = &recording.samples;
+    let count = samples.len() as f32;
+    match step {
+        FaceCalibrationStep::Neutral => {
+            calibration.neutral_x = samples.iter().map(|s| s.center_x).sum::<f32>() / count;
+            calibration.neutral_y = samples.iter().map(|s| s.center_y).sum::<f32>() / count;
+        }
+        FaceCalibrationStep::Left => {
+            calibration.left = samples.iter().map(|s| s.center_x).fold(f32::MAX, f32::min);
+        }
+        FaceCalibrationStep::Right => {
+            calibration.right = samples.iter().map(|s| s.center_x).fold(f32::MIN, f32::max);
+        }
+        FaceCalibrationStep::Up => {
+            calibration.up = samples.iter().map(|s| s.center_y).fold(f32::MAX, f32::min);
+        }
+        FaceCalibrationStep::Down => {
+            calibration.down = samples.iter().map(|s| s.center_y).fold(f32::MIN, f32::max);
+        }
+        FaceCalibrationStep::Distance(distance) => {
+            let mean = WebcamFacialData {
+                width: samples.iter().map(|s| s.width).sum::<f32>() / count,
+                ..Default::default()
+            };
+            calibration.face_width = Some(FaceDepth::face_width_at(
+                &mean,
+                webcam_facial.config_camera_fov,
+                distance,
+            ));
+        }
+    }
+    info!("Face calibration step {:?} done: {:?}", step, *calibration);
+    done.send(FaceCalibrationStepDone(step));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WebcamFacialPlugin;
+    use bevy::app::App;
+
+    #[test]
+    fn remap_scales_each_side_of_neutral_separately() {
+        assert!((remap(10.0, -20.0, 5.0, 15.0) - 0.5).abs() < 1e-5);
+        assert!((remap(-7.5, -20.0, 5.0, 15.0) + 0.5).abs() < 1e-5);
+        assert_eq!(remap(5.0, -20.0, 5.0, 15.0), 0.0);
+    }
+
+    #[test]
+    fn remap_clamps_beyond_extremes() {
+        assert_eq!(remap(30.0, -20.0, 5.0, 15.0), 1.0);
+        assert_eq!(remap(-40.0, -20.0, 5.0, 15.0), -1.0);
+    }
+
+    #[test]
+    fn remap_of_empty_range_is_zero() {
+        assert_eq!(remap(10.0, -20.0, 5.0, 5.0), 0.0);
+        assert!((remap(-7.5, -20.0, 5.0, 5.0) + 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn uncalibrated_maps_full_range() {
+        let calibration = FaceCalibration::default();
+        assert!((calibration.remap_x(25.0) - 0.5).abs() < 1e-5);
+        assert!((calibration.remap_y(-50.0) + 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn uncalibrated_neutral_is_frame_center() {
+        let calibration = FaceCalibration::uncalibrated(0.75);
+        assert_eq!(calibration.remap_y(frame_center_y(0.75)), 0.0);
+        assert!((calibration.remap_y(-50.0) + 1.0).abs() < 1e-5);
+        assert!((calibration.remap_y(25.0) - 1.0).abs() < 1e-5);
+        assert!((calibration.remap_y(6.25) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn plugin_inserts_calibration_of_its_frame() {
+        let mut app = App::new();
+        app.add_plugins(WebcamFacialPlugin {
+            config_webcam_width: 640,
+            config_webcam_height: 480,
+            config_webcam_autostart: false,
+            ..Default::default()
+        });
+        let calibration = app.world.resource::<FaceCalibration>();
+        assert_eq!(calibration.neutral_y, frame_center_y(0.75));
+        assert_eq!(calibration.down, 25.0);
+    }
+}
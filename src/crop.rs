@@ -0,0 +1,125 @@
+// Cropped, padded and resized texture of tracked face, Image side needs `textures` feature
+use bevy::ecs::system::Resource;
+#[cfg(feature = "textures")]
+use bevy::{
+    asset::{Assets, Handle, HandleUntyped},
+    ecs::{
+        event::{Event, EventReader, EventWriter},
+        system::{Res, ResMut},
+    },
+    reflect::TypeUuid,
+    render::texture::Image,
+};
+#[cfg(feature = "textures")]
+use std::sync::atomic::Ordering;
+
+use crate::{depth::face_to_frame, WebcamFacialData, WebcamPreviewFrame};
+#[cfg(feature = "textures")]
+use crate::{preview::frame_image, WebcamFacialController};
+
+#[cfg(feature = "textures")]
+pub const WEBCAM_FACE_CROP_IMAGE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 2971850373629158241);
+
+#[derive(Clone, Copy)]
+pub enum FaceCropType {
+    // Crop image is updated with every frame face is found
+    Live,
+    // Crop is taken only after CaptureFaceCrop event
+    OnDemand,
+    NoCrop,
+}
+
+// Handle of Image updated with live face crop
+#[cfg(feature = "textures")]
+#[derive(Resource, Clone)]
+pub struct WebcamFacialFaceCrop {
+    pub image: Handle<Image>,
+}
+
+#[cfg(feature = "textures")]
+impl Default for WebcamFacialFaceCrop {
+    fn default() -> Self {
+        Self {
+            image: WEBCAM_FACE_CROP_IMAGE_HANDLE.typed(),
+        }
+    }
+}
+
+// Send to take face crop from next frame with face found
+#[cfg(feature = "textures")]
+#[derive(Event)]
+pub struct CaptureFaceCrop;
+
+// Sent with new Image of face crop taken on demand
+#[cfg(feature = "textures")]
+#[derive(Event)]
+pub struct FaceCropCaptured(pub Handle<Image>);
+
+// Latest crop received from task, waiting for upload to Image
+#[derive(Resource, Default)]
+pub(crate) struct FaceCropFrameBuffer(pub Option<WebcamPreviewFrame>);
+
+// Square crop around face box grown by padding (part of face size on each side),
+// resized to `size` x `size` by skipping pixels. Area outside of frame is black.
+pub(crate) fn face_crop_frame(
+    face: &WebcamFacialData,
+    width: u32,
+    height: u32,
+    padding: f32,
+    size: u32,
+    pixel: impl Fn(u32, u32) -> [u8; 3],
+) -> WebcamPreviewFrame {
+    let pixels_per_unit = width as f32 / 100.0;
+    let center = face_to_frame(face.center_x, face.center_y, pixels_per_unit);
+    let crop_size = face.width.max(face.height) * pixels_per_unit * (1.0 + 2.0 * padding);
+    let left = center.x - crop_size / 2.0;
+    let top = center.y - crop_size / 2.0;
+    let step = crop_size / size as f32;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let frame_x = (left + x as f32 * step).floor();
+            let frame_y = (top + y as f32 * step).floor();
+            if frame_x < 0.0 || frame_y < 0.0 || frame_x >= width as f32 || frame_y >= height as f32
+            {
+                data.extend_from_slice(&[0, 0, 0, 255]);
+            } else {
+                let [r, g, b] = pixel(frame_x as u32, frame_y as u32);
+                data.extend_from_slice(&[r, g, b, 255]);
+            }
+        }
+    }
+    WebcamPreviewFrame {
+        width: size,
+        height: size,
+        data,
+    }
+}
+
+#[cfg(feature = "textures")]
+pub(crate) fn face_crop_system(
+    webcam_facial: Res<WebcamFacialController>,
+    face_crop: Res<WebcamFacialFaceCrop>,
+    mut requests: EventReader<CaptureFaceCrop>,
+    mut captured: EventWriter<FaceCropCaptured>,
+    mut frame_buffer: ResMut<FaceCropFrameBuffer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    // Ask webcam task for crop of next frame
+    if requests.iter().count() > 0 {
+        webcam_facial
+            .face_crop_requested
+            .store(true, Ordering::SeqCst);
+    }
+    if let Some(frame) = frame_buffer.0.take() {
+        match webcam_facial.config_face_crop_type {
+            FaceCropType::OnDemand => {
+                captured.send(FaceCropCaptured(images.add(frame_image(frame))));
+            }
+            _ => {
+                images.set_untracked(&face_crop.image, frame_image(frame));
+            }
+        }
+    }
+}
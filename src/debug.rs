@@ -0,0 +1,210 @@
+// Debug overlay drawing face detections with gizmos in screen space
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::Entity,
+        query::With,
+        schedule::IntoSystemConfigs,
+        system::{Commands, Local, Query, Res, Resource},
+    },
+    gizmos::gizmos::Gizmos,
+    math::{Vec2, Vec3},
+    render::{camera::Camera, color::Color},
+    text::TextStyle,
+    transform::components::GlobalTransform,
+    ui::{node_bundles::TextBundle, PositionType, Style, Val},
+    utils::default,
+};
+use std::collections::VecDeque;
+
+use crate::{
+    depth::face_to_frame, WebcamFacialController, WebcamFacialData, WebcamFacialSet,
+    WebcamFacialState,
+};
+
+// Add to plugins together with WebcamFacialPlugin, toggle with ResMut<WebcamFacialDebug>
+pub struct WebcamFacialDebugPlugin;
+
+impl Plugin for WebcamFacialDebugPlugin {
+    fn build(&self, app: &mut App) {
+        // Overlay is drawn every frame whatever config_schedule of WebcamFacialPlugin is
+        app.init_resource::<WebcamFacialDebug>().add_systems(
+            Update,
+            (
+                webcam_facial_debug_system.after(WebcamFacialSet::Process),
+                webcam_facial_debug_labels_system.after(WebcamFacialSet::Process),
+            ),
+        );
+    }
+}
+
+// Overlay settings. Place preview image at same position with width of 100 * scale to draw over it
+#[derive(Resource, Clone, Debug)]
+pub struct WebcamFacialDebug {
+    pub enabled: bool,
+    // Top left corner of overlay in logical window pixels
+    pub position: Vec2,
+    // Pixels per one unit of -50.0 .. 50.0 face coordinates
+    pub scale: f32,
+    // How many frames raw and filtered trajectory trails keep
+    pub trail_length: usize,
+}
+
+impl Default for WebcamFacialDebug {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            position: Vec2::new(10.0, 10.0),
+            scale: 3.0,
+            trail_length: 60,
+        }
+    }
+}
+
+impl WebcamFacialDebug {
+    // Face coordinates to overlay pixels, in camera frame orientation (not mirrored)
+    fn to_screen(&self, center_x: f32, center_y: f32) -> Vec2 {
+        self.position + face_to_frame(center_x, center_y, self.scale)
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct WebcamFacialDebugLabel;
+
+#[derive(Default)]
+pub(crate) struct WebcamFacialDebugTrails {
+    raw: VecDeque<Vec2>,
+    filtered: VecDeque<Vec2>,
+}
+
+fn webcam_facial_debug_system(
+    debug: Res<WebcamFacialDebug>,
+    webcam_facial: Res<WebcamFacialController>,
+    facial_state: Res<WebcamFacialState>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut trails: Local<WebcamFacialDebugTrails>,
+    mut gizmos: Gizmos,
+) {
+    if facial_state.is_changed() && facial_state.present {
+        let raw = debug.to_screen(facial_state.raw.center_x, facial_state.raw.center_y);
+        let filtered = debug.to_screen(
+            facial_state.filtered.center_x,
+            facial_state.filtered.center_y,
+        );
+        trails.raw.push_back(raw);
+        trails.filtered.push_back(filtered);
+        while trails.raw.len() > debug.trail_length {
+            trails.raw.pop_front();
+            trails.filtered.pop_front();
+        }
+    }
+    if !debug.enabled {
+        return;
+    }
+    // Draw on top camera just in front of its near plane
+    let (camera, camera_transform) = match cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .max_by_key(|(camera, _)| camera.order)
+    {
+        Some(camera) => camera,
+        None => return,
+    };
+    let to_world = |point: Vec2| -> Option<Vec3> {
+        camera
+            .viewport_to_world(camera_transform, point)
+            .map(|ray| ray.get_point(0.01))
+    };
+    let mut draw_box = |data: &WebcamFacialData, color: Color| {
+        let center = debug.to_screen(data.center_x, data.center_y);
+        let half_size = Vec2::new(data.width, data.height) * debug.scale / 2.0;
+        let corners = [
+            center + Vec2::new(-half_size.x, -half_size.y),
+            center + Vec2::new(half_size.x, -half_size.y),
+            center + Vec2::new(half_size.x, half_size.y),
+            center + Vec2::new(-half_size.x, half_size.y),
+            center + Vec2::new(-half_size.x, -half_size.y),
+        ];
+        gizmos.linestrip(corners.into_iter().filter_map(to_world), color);
+    };
+    // Camera frame outline, face y coordinates are scaled by frame width
+    let frame_height =
+        100.0 * webcam_facial.config_height as f32 / webcam_facial.config_width as f32;
+    draw_box(
+        &WebcamFacialData {
+            center_y: frame_height / 2.0 - 50.0,
+            width: 100.0,
+            height: frame_height,
+            ..default()
+        },
+        Color::GRAY,
+    );
+    for face in facial_state.faces.iter() {
+        draw_box(face, Color::YELLOW);
+    }
+    if facial_state.present {
+        draw_box(&facial_state.raw, Color::RED);
+    }
+    draw_box(&facial_state.filtered, Color::GREEN);
+    // Landmarks as small crosses, their x is not mirrored as face center
+    for point in facial_state.landmarks.points.iter() {
+        let center = debug.to_screen(-point.x, point.y);
+        let size = debug.scale / 2.0;
+        for offset in [Vec2::new(size, 0.0), Vec2::new(0.0, size)] {
+            gizmos.linestrip(
+                [center - offset, center + offset]
+                    .into_iter()
+                    .filter_map(to_world),
+                Color::CYAN,
+            );
+        }
+    }
+    gizmos.linestrip(trails.raw.iter().copied().filter_map(to_world), Color::RED);
+    gizmos.linestrip(
+        trails.filtered.iter().copied().filter_map(to_world),
+        Color::GREEN,
+    );
+}
+
+// Score labels above detected faces, recreated when new data arrives
+fn webcam_facial_debug_labels_system(
+    mut commands: Commands,
+    debug: Res<WebcamFacialDebug>,
+    facial_state: Res<WebcamFacialState>,
+    labels: Query<Entity, With<WebcamFacialDebugLabel>>,
+) {
+    if !facial_state.is_changed() && !debug.is_changed() {
+        return;
+    }
+    for entity in labels.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !debug.enabled {
+        return;
+    }
+    for face in facial_state.faces.iter() {
+        let position = debug.to_screen(
+            face.center_x + face.width / 2.0,
+            face.center_y - face.height / 2.0,
+        );
+        commands.spawn((
+            TextBundle::from_section(
+                format!("{:.1}", face.score),
+                TextStyle {
+                    font_size: 14.0,
+                    color: Color::YELLOW,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(position.x),
+                top: Val::Px(position.y - 16.0),
+                ..default()
+            }),
+            WebcamFacialDebugLabel,
+        ));
+    }
+}
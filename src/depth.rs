@@ -0,0 +1,113 @@
+// Distance estimation from face size, using camera field of view and average face width
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        system::{Res, ResMut, Resource},
+    },
+    math::{Vec2, Vec3},
+};
+
+use crate::{FaceCalibration, WebcamFacialController, WebcamFacialData, WebcamFacialState};
+
+// Estimated head position in camera space, meters.
+// x grows to the right and y upwards as face center, z is distance from camera towards player
+#[derive(Resource, Default, Clone, Debug)]
+pub struct FaceDepth {
+    pub distance: f32,
+    pub head_position: Vec3,
+}
+
+impl FaceDepth {
+    // Pinhole camera model, face width is in -50.0 .. 50.0 units of frame width.
+    // Aspect ratio is frame height to width (0.75 for 640x480), it moves vertical frame center.
+    pub fn estimate(
+        data: &WebcamFacialData,
+        camera_fov: f32,
+        face_width: f32,
+        aspect_ratio: f32,
+    ) -> Self {
+        if data.width <= 0.0 {
+            return Self::default();
+        }
+        let tan_half_fov = (camera_fov.to_radians() / 2.0).tan();
+        let distance = face_width * 50.0 / (data.width * tan_half_fov);
+        Self {
+            distance,
+            head_position: Vec3::new(
+                data.center_x / 50.0 * tan_half_fov * distance,
+                -(data.center_y - frame_center_y(aspect_ratio)) / 50.0 * tan_half_fov * distance,
+                distance,
+            ),
+        }
+    }
+
+    // Face width in meters which gives known distance for given face data
+    pub fn face_width_at(data: &WebcamFacialData, camera_fov: f32, distance: f32) -> f32 {
+        let tan_half_fov = (camera_fov.to_radians() / 2.0).tan();
+        distance * data.width * tan_half_fov / 50.0
+    }
+}
+
+// Face center_y of frame center, y is scaled by frame width so it is 0.0 only for square frames
+pub(crate) fn frame_center_y(aspect_ratio: f32) -> f32 {
+    50.0 * aspect_ratio - 50.0
+}
+
+// Face coordinates back to frame position at given pixels per unit, center x is mirrored back
+// to camera frame orientation and y is scaled by frame width as well
+pub(crate) fn face_to_frame(center_x: f32, center_y: f32, pixels_per_unit: f32) -> Vec2 {
+    Vec2::new(50.0 - center_x, center_y + 50.0) * pixels_per_unit
+}
+
+pub(crate) fn face_depth_system(
+    webcam_facial: Res<WebcamFacialController>,
+    calibration: Res<FaceCalibration>,
+    facial_state: Res<WebcamFacialState>,
+    mut depth: ResMut<FaceDepth>,
+) {
+    if !facial_state.is_changed() && !calibration.is_changed() {
+        return;
+    }
+    if !facial_state.present {
+        return;
+    }
+    // Per-user calibrated face width overrides average one
+    let face_width = calibration
+        .face_width
+        .unwrap_or(webcam_facial.config_face_width);
+    *depth = FaceDepth::estimate(
+        &facial_state.filtered,
+        webcam_facial.config_camera_fov,
+        face_width,
+        webcam_facial.config_height as f32 / webcam_facial.config_width as f32,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_in_frame_center_is_on_camera_axis() {
+        // 640x480 frame, face centered vertically
+        let data = WebcamFacialData {
+            center_y: frame_center_y(0.75),
+            width: 20.0,
+            ..Default::default()
+        };
+        let depth = FaceDepth::estimate(&data, 60.0, 0.15, 0.75);
+        assert!(depth.head_position.y.abs() < 1e-5);
+        assert!((depth.head_position.z - depth.distance).abs() < 1e-5);
+    }
+
+    #[test]
+    fn face_width_at_inverts_estimate() {
+        let data = WebcamFacialData {
+            width: 12.5,
+            ..Default::default()
+        };
+        let face_width = FaceDepth::face_width_at(&data, 60.0, 0.8);
+        let depth = FaceDepth::estimate(&data, 60.0, face_width, 0.75);
+        assert!((depth.distance - 0.8).abs() < 1e-4);
+    }
+}
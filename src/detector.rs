@@ -0,0 +1,109 @@
+// Face detector backends, rustface is used by default
+use bevy::log::info;
+use rustface::ImageData;
+
+#[cfg(feature = "onnx")]
+use crate::onnx::UltraFaceDetector;
+
+// Captured frame passed to detector
+pub struct DetectorFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    // RGB bytes, 3 per pixel, row by row
+    pub rgb: &'a [u8],
+    // Grayscale bytes, 1 per pixel, row by row
+    pub grayscale: &'a [u8],
+}
+
+// Face box in frame pixels with detector specific score
+#[derive(Clone, Debug, Default)]
+pub struct FaceDetection {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub score: f32,
+}
+
+// Implement to plug another face detection model into webcam task
+pub trait FaceDetector {
+    fn detect(&mut self, frame: &DetectorFrame) -> Vec<FaceDetection>;
+}
+
+#[derive(Clone, Debug)]
+pub enum FaceDetectorType {
+    // SeetaFace model via rustface
+    Rustface {
+        model_path: String,
+        // Smallest face size in pixels to look for
+        min_face_size: u32,
+        score_threshold: f64,
+    },
+    // UltraFace RFB-320 ONNX model, better with profile and tilted faces. Score is 0.0 .. 1.0
+    #[cfg(feature = "onnx")]
+    UltraFace {
+        model_path: String,
+        score_threshold: f32,
+    },
+    // Detector created by function in webcam task every time it starts
+    Custom(fn() -> Result<Box<dyn FaceDetector>, String>),
+}
+
+impl Default for FaceDetectorType {
+    fn default() -> Self {
+        FaceDetectorType::Rustface {
+            model_path: "assets/NN_Models/seeta.bin".to_string(),
+            min_face_size: 20,
+            score_threshold: 2.0,
+        }
+    }
+}
+
+impl FaceDetectorType {
+    pub(crate) fn create(&self) -> Result<Box<dyn FaceDetector>, String> {
+        match self {
+            FaceDetectorType::Rustface {
+                model_path,
+                min_face_size,
+                score_threshold,
+            } => {
+                let mut detector =
+                    rustface::create_detector(model_path).map_err(|error| error.to_string())?;
+                info!("Using {} recognition model.", model_path);
+                detector.set_min_face_size(*min_face_size);
+                detector.set_score_thresh(*score_threshold);
+                detector.set_pyramid_scale_factor(0.8);
+                detector.set_slide_window_step(4, 4);
+                Ok(Box::new(RustfaceDetector(detector)))
+            }
+            #[cfg(feature = "onnx")]
+            FaceDetectorType::UltraFace {
+                model_path,
+                score_threshold,
+            } => match UltraFaceDetector::new(model_path, *score_threshold) {
+                Ok(detector) => Ok(Box::new(detector)),
+                Err(error) => Err(error.to_string()),
+            },
+            FaceDetectorType::Custom(create) => create(),
+        }
+    }
+}
+
+struct RustfaceDetector(Box<dyn rustface::Detector>);
+
+impl FaceDetector for RustfaceDetector {
+    fn detect(&mut self, frame: &DetectorFrame) -> Vec<FaceDetection> {
+        let image_data = ImageData::new(frame.grayscale, frame.width, frame.height);
+        self.0
+            .detect(&image_data)
+            .iter()
+            .map(|face| FaceDetection {
+                x: face.bbox().x() as f32,
+                y: face.bbox().y() as f32,
+                width: face.bbox().width() as f32,
+                height: face.bbox().height() as f32,
+                score: face.score() as f32,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,125 @@
+// Emotion classification of selected face, smoothed over time in webcam task
+use crate::{onnx::FerPlusClassifier, DetectorFrame, FaceDetection};
+
+// Implement to plug another emotion model into webcam task.
+// Returns probabilities of face in frame pixels, None when classification failed.
+pub trait EmotionClassifier {
+    fn classify(&mut self, frame: &DetectorFrame, face: &FaceDetection) -> Option<FaceEmotions>;
+}
+
+#[derive(Clone, Debug)]
+pub enum EmotionClassifierType {
+    // FER+ ONNX model run on grayscale 64x64 crop of face
+    FerPlus { model_path: String },
+    // Classifier created by function in webcam task every time it starts
+    Custom(fn() -> Result<Box<dyn EmotionClassifier>, String>),
+    NoEmotions,
+}
+
+impl EmotionClassifierType {
+    pub(crate) fn create(&self) -> Result<Option<Box<dyn EmotionClassifier>>, String> {
+        match self {
+            EmotionClassifierType::FerPlus { model_path } => {
+                match FerPlusClassifier::new(model_path) {
+                    Ok(classifier) => Ok(Some(Box::new(classifier))),
+                    Err(error) => Err(error.to_string()),
+                }
+            }
+            EmotionClassifierType::Custom(create) => create().map(Some),
+            EmotionClassifierType::NoEmotions => Ok(None),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emotion {
+    Neutral,
+    Happy,
+    Surprised,
+    Sad,
+    Angry,
+    Disgusted,
+    Fearful,
+    Contemptuous,
+}
+
+// Probabilities 0.0 .. 1.0 of each emotion, summing up to 1.0 (all zero before first face)
+#[derive(Default, Clone, Copy, Debug)]
+pub struct FaceEmotions {
+    pub neutral: f32,
+    pub happy: f32,
+    pub surprised: f32,
+    pub sad: f32,
+    pub angry: f32,
+    pub disgusted: f32,
+    pub fearful: f32,
+    pub contemptuous: f32,
+}
+
+impl FaceEmotions {
+    // Probabilities in Emotion order
+    pub fn from_array(probabilities: [f32; 8]) -> Self {
+        let [neutral, happy, surprised, sad, angry, disgusted, fearful, contemptuous] =
+            probabilities;
+        Self {
+            neutral,
+            happy,
+            surprised,
+            sad,
+            angry,
+            disgusted,
+            fearful,
+            contemptuous,
+        }
+    }
+
+    pub fn to_array(&self) -> [f32; 8] {
+        [
+            self.neutral,
+            self.happy,
+            self.surprised,
+            self.sad,
+            self.angry,
+            self.disgusted,
+            self.fearful,
+            self.contemptuous,
+        ]
+    }
+
+    pub fn probability(&self, emotion: Emotion) -> f32 {
+        self.to_array()[emotion as usize]
+    }
+
+    // Most probable emotion with its probability
+    pub fn dominant(&self) -> (Emotion, f32) {
+        const EMOTIONS: [Emotion; 8] = [
+            Emotion::Neutral,
+            Emotion::Happy,
+            Emotion::Surprised,
+            Emotion::Sad,
+            Emotion::Angry,
+            Emotion::Disgusted,
+            Emotion::Fearful,
+            Emotion::Contemptuous,
+        ];
+        EMOTIONS
+            .into_iter()
+            .zip(self.to_array())
+            .fold((Emotion::Neutral, 0.0), |best, current| {
+                if current.1 > best.1 {
+                    current
+                } else {
+                    best
+                }
+            })
+    }
+
+    // Low pass filter, alpha 1.0 takes new probabilities as they are
+    pub(crate) fn smooth(&mut self, new: &FaceEmotions, alpha: f32) {
+        let mut current = self.to_array();
+        for (value, new_value) in current.iter_mut().zip(new.to_array()) {
+            *value += (new_value - *value) * alpha;
+        }
+        *self = Self::from_array(current);
+    }
+}
@@ -0,0 +1,70 @@
+// Mouth open and smile threshold events from filtered face data
+use bevy::ecs::{
+    change_detection::DetectChanges,
+    event::{Event, EventWriter},
+    system::{Local, Res},
+};
+
+use crate::{WebcamFacialController, WebcamFacialState};
+
+#[derive(Clone, Copy, Debug)]
+pub enum ExpressionDetectionType {
+    // Events are sent when filtered mouth_open or smile crosses these values
+    Threshold { mouth_open: f32, smile: f32 },
+    NoExpressionEvents,
+}
+
+// Sent when mouth gets open or closed
+#[derive(Event, Clone, Debug)]
+pub struct MouthOpenEvent {
+    pub open: bool,
+}
+
+// Sent when player starts or stops smiling
+#[derive(Event, Clone, Debug)]
+pub struct SmileEvent {
+    pub smiling: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct ExpressionTracker {
+    mouth_open: bool,
+    smiling: bool,
+}
+
+pub(crate) fn face_expression_system(
+    webcam_facial: Res<WebcamFacialController>,
+    facial_state: Res<WebcamFacialState>,
+    mut tracker: Local<ExpressionTracker>,
+    mut mouth_events: EventWriter<MouthOpenEvent>,
+    mut smile_events: EventWriter<SmileEvent>,
+) {
+    if !facial_state.is_changed() {
+        return;
+    }
+    let (mouth_open_threshold, smile_threshold) = match webcam_facial.config_expression_type {
+        ExpressionDetectionType::Threshold { mouth_open, smile } => (mouth_open, smile),
+        ExpressionDetectionType::NoExpressionEvents => return,
+    };
+    // Lost face ends open mouth and smile, start over when it is back
+    if !facial_state.present {
+        if tracker.mouth_open {
+            mouth_events.send(MouthOpenEvent { open: false });
+        }
+        if tracker.smiling {
+            smile_events.send(SmileEvent { smiling: false });
+        }
+        *tracker = ExpressionTracker::default();
+        return;
+    }
+    let mouth_open = facial_state.filtered.mouth_open > mouth_open_threshold;
+    if mouth_open != tracker.mouth_open {
+        tracker.mouth_open = mouth_open;
+        mouth_events.send(MouthOpenEvent { open: mouth_open });
+    }
+    let smiling = facial_state.filtered.smile > smile_threshold;
+    if smiling != tracker.smiling {
+        tracker.smiling = smiling;
+        smile_events.send(SmileEvent { smiling });
+    }
+}
@@ -1,6 +1,13 @@
 use crate::WebcamFacialData;
 
-pub struct WebcamFacialDataFiltered(Vec<WebcamFacialData>, u32, SmoothingFilterType);
+// Keeps last frames data and smooths position, size and score separately
+pub struct WebcamFacialDataFiltered {
+    data: Vec<WebcamFacialData>,
+    length: u32,
+    position_filter: SmoothingFilterType,
+    size_filter: SmoothingFilterType,
+    score_filter: SmoothingFilterType,
+}
 
 #[allow(unused)]
 #[derive(Clone, Copy)]
@@ -11,86 +18,127 @@ pub enum SmoothingFilterType {
 }
 
 impl WebcamFacialDataFiltered {
-    pub fn new(length: u32, filter_type: SmoothingFilterType) -> Self {
-        Self(Vec::new(), length, filter_type)
+    pub fn new(
+        length: u32,
+        position_filter: SmoothingFilterType,
+        size_filter: SmoothingFilterType,
+        score_filter: SmoothingFilterType,
+    ) -> Self {
+        Self {
+            data: Vec::new(),
+            length,
+            position_filter,
+            size_filter,
+            score_filter,
+        }
     }
 
     #[allow(unused)]
     pub fn push(&mut self, data: WebcamFacialData) {
-        if self.0.len() >= self.1 as usize {
-            self.0.remove(0); // Remove the oldest element
+        if self.data.len() >= self.length as usize {
+            self.data.remove(0); // Remove the oldest element
         }
-        self.0.push(data);
+        self.data.push(data);
     }
 
     #[allow(unused)]
     pub fn get(&mut self) -> WebcamFacialData {
-        if self.0.len() == 0 {
+        if self.data.is_empty() {
             self.push(WebcamFacialData::default());
         }
-        match self.2 {
-            SmoothingFilterType::MeanMedian => return self.mean_median_filter(),
-            SmoothingFilterType::LowPass(alpha) => return self.low_pass_filter(alpha),
-            SmoothingFilterType::NoFilter => {
-                return WebcamFacialData {
-                    center_x: self.0[self.0.len() - 1].center_x,
-                    center_y: self.0[self.0.len() - 1].center_y,
-                    x: self.0[self.0.len() - 1].x,
-                    y: self.0[self.0.len() - 1].y,
-                    width: self.0[self.0.len() - 1].width,
-                    height: self.0[self.0.len() - 1].height,
-                    score: self.0[self.0.len() - 1].score,
-                }
-            }
+        let center_x = self.filter(self.position_filter, |data| data.center_x);
+        let center_y = self.filter(self.position_filter, |data| data.center_y);
+        let width = self.filter(self.size_filter, |data| data.width);
+        let height = self.filter(self.size_filter, |data| data.height);
+        let score = self.filter(self.score_filter, |data| data.score);
+        let yaw = self.filter(self.position_filter, |data| data.yaw);
+        let pitch = self.filter(self.position_filter, |data| data.pitch);
+        let roll = self.filter(self.position_filter, |data| data.roll);
+        let mouth_open = self.filter(self.position_filter, |data| data.mouth_open);
+        let smile = self.filter(self.position_filter, |data| data.smile);
+
+        // Rectangle corner is taken from filtered center and size so it stays consistent with both
+        WebcamFacialData {
+            center_x,
+            center_y,
+            x: -center_x - width / 2.0,
+            y: center_y - height / 2.0,
+            width,
+            height,
+            score,
+            yaw,
+            pitch,
+            roll,
+            mouth_open,
+            smile,
+        }
+    }
+
+    fn filter(&self, filter_type: SmoothingFilterType, value: fn(&WebcamFacialData) -> f32) -> f32 {
+        match filter_type {
+            SmoothingFilterType::MeanMedian => self.mean_median_filter(value),
+            SmoothingFilterType::LowPass(alpha) => self.low_pass_filter(alpha, value),
+            SmoothingFilterType::NoFilter => value(&self.data[self.data.len() - 1]),
         }
     }
 
     #[allow(unused)]
-    fn low_pass_filter(&self, alpha: f32) -> WebcamFacialData {
-        let mut filtered_data = WebcamFacialData::default();
-        for data in &self.0 {
-            filtered_data.center_x += alpha * (data.center_x - filtered_data.center_x);
-            filtered_data.center_y += alpha * (data.center_y - filtered_data.center_y);
-            filtered_data.x += alpha * (data.x - filtered_data.x);
-            filtered_data.y += alpha * (data.y - filtered_data.y);
-            filtered_data.width += alpha * (data.width - filtered_data.width);
-            filtered_data.height += alpha * (data.height - filtered_data.height);
-            filtered_data.score += alpha * (data.score - filtered_data.score);
+    fn low_pass_filter(&self, alpha: f32, value: fn(&WebcamFacialData) -> f32) -> f32 {
+        // Start from oldest sample, so filter settles on constant input
+        let mut filtered_value = value(&self.data[0]);
+        for data in &self.data[1..] {
+            filtered_value += alpha * (value(data) - filtered_value);
         }
 
-        filtered_data
+        filtered_value
     }
 
     #[allow(unused)]
-    fn mean_median_filter(&self) -> WebcamFacialData {
-        let num_elements = self.0.len();
-
-        let mut center_x_sum = 0.0;
-        let mut center_y_sum = 0.0;
-        let mut x_sum = 0.0;
-        let mut y_sum = 0.0;
-        let mut width_sum = 0.0;
-        let mut height_sum = 0.0;
-        let mut score_sum = 0.0;
-
-        for data in &self.0 {
-            center_x_sum += data.center_x;
-            center_y_sum += data.center_y;
-            x_sum += data.x;
-            y_sum += data.y;
-            width_sum += data.width;
-            height_sum += data.height;
-            score_sum += data.score;
+    fn mean_median_filter(&self, value: fn(&WebcamFacialData) -> f32) -> f32 {
+        let num_elements = self.data.len();
+
+        let mut value_sum = 0.0;
+        for data in &self.data {
+            value_sum += value(data);
         }
 
-        WebcamFacialData {
-            center_x: center_x_sum / num_elements as f32,
-            center_y: center_y_sum / num_elements as f32,
-            x: x_sum / num_elements as f32,
-            y: y_sum / num_elements as f32,
-            width: width_sum / num_elements as f32,
-            height: height_sum / num_elements as f32,
-            score: score_sum / num_elements as f32,
+        value_sum / num_elements as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filtered(filter_type: SmoothingFilterType, length: u32, widths: &[f32]) -> f32 {
+        let mut filter =
+            WebcamFacialDataFiltered::new(length, filter_type, filter_type, filter_type);
+        for width in widths {
+            filter.push(WebcamFacialData {
+                width: *width,
+                ..Default::default()
+            });
         }
+        filter.get().width
+    }
+
+    #[test]
+    fn low_pass_settles_on_constant_input() {
+        let width = filtered(SmoothingFilterType::LowPass(0.01), 20, &[30.0; 20]);
+        assert!((width - 30.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn low_pass_lags_behind_step() {
+        let mut widths = vec![10.0; 9];
+        widths.push(20.0);
+        let width = filtered(SmoothingFilterType::LowPass(0.1), 10, &widths);
+        assert!((width - 11.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn mean_median_keeps_last_frames() {
+        let width = filtered(SmoothingFilterType::MeanMedian, 2, &[100.0, 10.0, 20.0]);
+        assert!((width - 15.0).abs() < 1e-4);
     }
 }
@@ -0,0 +1,129 @@
+// Declarative driving of entity Transforms from face data
+use bevy::{
+    ecs::{
+        component::Component,
+        system::{Query, Res},
+    },
+    math::{EulerRot, Quat},
+    transform::components::Transform,
+};
+
+use crate::{FaceDepth, FaceInput, FaceState, WebcamFacialState};
+
+#[derive(Clone, Copy, Debug)]
+pub enum FaceSource {
+    // Filtered (or interpolated if enabled) face data in -50.0 .. 50.0 units
+    CenterX,
+    CenterY,
+    Width,
+    Height,
+    Score,
+    // Head pose in degrees, needs landmarks
+    Yaw,
+    Pitch,
+    Roll,
+    // Mouth openness ratio and smile score, need 68 point landmarks
+    MouthOpen,
+    Smile,
+    // Mapped game input axes from FaceInput
+    InputX,
+    InputY,
+    // Estimated distance from FaceDepth in meters
+    Distance,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FaceTarget {
+    TranslationX,
+    TranslationY,
+    TranslationZ,
+    // Rotation angles in degrees
+    RotationYaw,
+    RotationPitch,
+    RotationRoll,
+    // Uniform scale factor added to 1.0
+    Scale,
+}
+
+// Value taken from source is multiplied by scale and added to offset,
+// then added to Transform entity had when it got FaceDriven
+#[derive(Clone, Copy, Debug)]
+pub struct FaceMapping {
+    pub source: FaceSource,
+    pub target: FaceTarget,
+    pub scale: f32,
+    pub offset: f32,
+}
+
+// Attach to entity to drive its Transform from face data
+#[derive(Component, Clone, Default, Debug)]
+pub struct FaceDriven {
+    pub mappings: Vec<FaceMapping>,
+    base: Option<Transform>,
+}
+
+impl FaceDriven {
+    pub fn map(mut self, source: FaceSource, target: FaceTarget, scale: f32) -> Self {
+        self.mappings.push(FaceMapping {
+            source,
+            target,
+            scale,
+            offset: 0.0,
+        });
+        self
+    }
+}
+
+pub(crate) fn face_driven_system(
+    facial_state: Res<WebcamFacialState>,
+    face_state: Option<Res<FaceState>>,
+    input: Res<FaceInput>,
+    depth: Res<FaceDepth>,
+    mut query: Query<(&mut FaceDriven, &mut Transform)>,
+) {
+    // Prefer render-rate interpolated data if enabled
+    let data = match &face_state {
+        Some(face_state) => &face_state.data,
+        None => &facial_state.filtered,
+    };
+    for (mut driven, mut transform) in query.iter_mut() {
+        // Remember starting Transform, mappings are applied relative to it
+        if driven.base.is_none() {
+            driven.base = Some(*transform);
+        }
+        let base = driven.base.unwrap();
+        let mut translation = base.translation;
+        let (mut yaw, mut pitch, mut roll) = (0.0, 0.0, 0.0);
+        let mut scale = 1.0;
+        for mapping in driven.mappings.iter() {
+            let value = match mapping.source {
+                FaceSource::CenterX => data.center_x,
+                FaceSource::CenterY => data.center_y,
+                FaceSource::Width => data.width,
+                FaceSource::Height => data.height,
+                FaceSource::Score => data.score,
+                FaceSource::Yaw => data.yaw,
+                FaceSource::Pitch => data.pitch,
+                FaceSource::Roll => data.roll,
+                FaceSource::MouthOpen => data.mouth_open,
+                FaceSource::Smile => data.smile,
+                FaceSource::InputX => input.x,
+                FaceSource::InputY => input.y,
+                FaceSource::Distance => depth.distance,
+            };
+            let amount = value * mapping.scale + mapping.offset;
+            match mapping.target {
+                FaceTarget::TranslationX => translation.x += amount,
+                FaceTarget::TranslationY => translation.y += amount,
+                FaceTarget::TranslationZ => translation.z += amount,
+                FaceTarget::RotationYaw => yaw += amount.to_radians(),
+                FaceTarget::RotationPitch => pitch += amount.to_radians(),
+                FaceTarget::RotationRoll => roll += amount.to_radians(),
+                FaceTarget::Scale => scale += amount,
+            }
+        }
+        transform.translation = translation;
+        transform.rotation = base.rotation * Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+        transform.scale = base.scale * scale;
+    }
+}
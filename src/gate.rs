@@ -0,0 +1,163 @@
+use crate::WebcamFacialData;
+
+// Rejects single frame detections that jump too far away from current track
+pub struct WebcamFacialDataGate {
+    gate_type: OutlierGateType,
+    track: Option<WebcamFacialData>,
+    candidate: Option<WebcamFacialData>,
+    candidate_frames: u32,
+}
+
+#[derive(Clone, Copy)]
+pub enum OutlierGateType {
+    // Maximum allowed jump of face center and face size (in -50.0 .. 50.0 units)
+    // and for how many frames jump must persist to be accepted as new track
+    Threshold {
+        position: f32,
+        size: f32,
+        frames: u32,
+    },
+    NoGate,
+}
+
+impl WebcamFacialDataGate {
+    pub fn new(gate_type: OutlierGateType) -> Self {
+        Self {
+            gate_type,
+            track: None,
+            candidate: None,
+            candidate_frames: 0,
+        }
+    }
+
+    // Returns true if data is accepted, false if it is rejected as outlier
+    pub fn check(&mut self, data: &WebcamFacialData) -> bool {
+        let (position, size, frames) = match self.gate_type {
+            OutlierGateType::Threshold {
+                position,
+                size,
+                frames,
+            } => (position, size, frames),
+            OutlierGateType::NoGate => return true,
+        };
+        // Frames without face are not gated and do not change track
+        if data.score <= 0.0 {
+            return true;
+        }
+        let track = match &self.track {
+            Some(track) => track,
+            None => {
+                self.track = Some(data.clone());
+                return true;
+            }
+        };
+        if !is_jump(track, data, position, size) {
+            self.track = Some(data.clone());
+            self.candidate = None;
+            self.candidate_frames = 0;
+            return true;
+        }
+        // Jump detected, count how long new position persists
+        match &self.candidate {
+            Some(candidate) if !is_jump(candidate, data, position, size) => {
+                self.candidate_frames += 1;
+            }
+            _ => {
+                self.candidate_frames = 1;
+            }
+        }
+        self.candidate = Some(data.clone());
+        if self.candidate_frames >= frames {
+            // Jump persisted long enough, take it as new track
+            self.track = Some(data.clone());
+            self.candidate = None;
+            self.candidate_frames = 0;
+            return true;
+        }
+        false
+    }
+}
+
+fn is_jump(from: &WebcamFacialData, to: &WebcamFacialData, position: f32, size: f32) -> bool {
+    let dx = to.center_x - from.center_x;
+    let dy = to.center_y - from.center_y;
+    let position_jump = (dx * dx + dy * dy).sqrt();
+    let size_jump = (to.width - from.width)
+        .abs()
+        .max((to.height - from.height).abs());
+    position_jump > position || size_jump > size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(center_x: f32, width: f32) -> WebcamFacialData {
+        WebcamFacialData {
+            center_x,
+            width,
+            height: width,
+            score: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn threshold_gate(frames: u32) -> WebcamFacialDataGate {
+        WebcamFacialDataGate::new(OutlierGateType::Threshold {
+            position: 20.0,
+            size: 15.0,
+            frames,
+        })
+    }
+
+    #[test]
+    fn accepts_movement_within_threshold() {
+        let mut gate = threshold_gate(3);
+        assert!(gate.check(&face(0.0, 20.0)));
+        assert!(gate.check(&face(20.0, 20.0)));
+        assert!(gate.check(&face(20.0, 35.0)));
+    }
+
+    #[test]
+    fn rejects_position_and_size_jumps() {
+        let mut gate = threshold_gate(3);
+        assert!(gate.check(&face(0.0, 20.0)));
+        assert!(!gate.check(&face(20.5, 20.0)));
+        assert!(!gate.check(&face(0.0, 35.5)));
+        // Track is kept, so face back at old position is accepted
+        assert!(gate.check(&face(1.0, 20.0)));
+    }
+
+    #[test]
+    fn accepts_jump_on_nth_persisting_frame() {
+        let mut gate = threshold_gate(3);
+        assert!(gate.check(&face(0.0, 20.0)));
+        assert!(!gate.check(&face(40.0, 20.0)));
+        assert!(!gate.check(&face(41.0, 20.0)));
+        assert!(gate.check(&face(40.0, 20.0)));
+        // New track follows jumped position
+        assert!(gate.check(&face(42.0, 20.0)));
+        assert!(!gate.check(&face(0.0, 20.0)));
+    }
+
+    #[test]
+    fn restarts_persistence_when_candidate_jumps() {
+        let mut gate = threshold_gate(2);
+        assert!(gate.check(&face(0.0, 20.0)));
+        assert!(!gate.check(&face(40.0, 20.0)));
+        assert!(!gate.check(&face(-40.0, 20.0)));
+        assert!(gate.check(&face(-40.0, 20.0)));
+    }
+
+    #[test]
+    fn does_not_gate_missing_face_or_disabled_gate() {
+        let mut gate = threshold_gate(3);
+        assert!(gate.check(&face(0.0, 20.0)));
+        assert!(gate.check(&WebcamFacialData::default()));
+        assert!(!gate.check(&face(40.0, 20.0)));
+
+        let mut gate = WebcamFacialDataGate::new(OutlierGateType::NoGate);
+        assert!(gate.check(&face(0.0, 20.0)));
+        assert!(gate.check(&face(40.0, 20.0)));
+    }
+}
@@ -0,0 +1,237 @@
+// Head gestures recognized over filtered face trajectory and head pose
+use bevy::ecs::{
+    change_detection::DetectChanges,
+    event::{Event, EventWriter},
+    system::{Local, Res},
+};
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{WebcamFacialController, WebcamFacialData, WebcamFacialState};
+
+// Movement needed at sensitivity 0.5. Pose angles are used with landmarks, face center otherwise.
+const NOD_PITCH: f32 = 8.0;
+const NOD_CENTER_Y: f32 = 3.0;
+const SHAKE_YAW: f32 = 10.0;
+const SHAKE_CENTER_X: f32 = 4.0;
+const TILT_ROLL: f32 = 15.0;
+// Face width change as part of width at window start
+const LEAN_WIDTH: f32 = 0.15;
+// Direction changes making nod or shake (down and back up is one), more raise confidence
+const MIN_SWINGS: usize = 1;
+const FULL_SWINGS: usize = 3;
+
+#[derive(Clone, Copy, Debug)]
+pub enum HeadGestureType {
+    // Sensitivity 0.0 .. 1.0 scales movement needed, higher reacts to smaller movements.
+    // Nod, shake and lean have to happen within window seconds.
+    Recognize { sensitivity: f32, window: f32 },
+    NoGestures,
+}
+
+// Recognized gesture with confidence 0.0 .. 1.0. Tilts are to player's own side.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum HeadGestureEvent {
+    Nod { confidence: f32 },
+    Shake { confidence: f32 },
+    TiltLeft { confidence: f32 },
+    TiltRight { confidence: f32 },
+    LeanIn { confidence: f32 },
+    LeanOut { confidence: f32 },
+}
+
+#[derive(Default)]
+pub(crate) struct GestureTracker {
+    history: VecDeque<(Duration, WebcamFacialData)>,
+    tilted: bool,
+}
+
+pub(crate) fn face_gesture_system(
+    webcam_facial: Res<WebcamFacialController>,
+    facial_state: Res<WebcamFacialState>,
+    mut tracker: Local<GestureTracker>,
+    mut gestures: EventWriter<HeadGestureEvent>,
+) {
+    if !facial_state.is_changed() {
+        return;
+    }
+    let (sensitivity, window) = match webcam_facial.config_gesture_type {
+        HeadGestureType::Recognize {
+            sensitivity,
+            window,
+        } => (sensitivity, Duration::from_secs_f32(window)),
+        HeadGestureType::NoGestures => return,
+    };
+    if !facial_state.present {
+        tracker.history.clear();
+        tracker.tilted = false;
+        return;
+    }
+    let now = facial_state.timestamp;
+    tracker
+        .history
+        .push_back((now, facial_state.filtered.clone()));
+    while let Some((timestamp, _)) = tracker.history.front() {
+        if now.saturating_sub(*timestamp) <= window {
+            break;
+        }
+        tracker.history.pop_front();
+    }
+    // 0.5 keeps base thresholds, 1.0 halves them and 0.0 makes them 1.5 times larger
+    let scale = 1.5 - sensitivity.clamp(0.0, 1.0);
+    let pose = !facial_state.landmarks.points.is_empty();
+
+    // Tilt is a held pose, sent once when crossing threshold
+    if pose {
+        let roll = facial_state.filtered.roll;
+        let threshold = TILT_ROLL * scale;
+        if roll.abs() < threshold {
+            tracker.tilted = false;
+        } else if !tracker.tilted {
+            tracker.tilted = true;
+            let confidence = confidence(roll.abs(), threshold);
+            gestures.send(if roll < 0.0 {
+                HeadGestureEvent::TiltLeft { confidence }
+            } else {
+                HeadGestureEvent::TiltRight { confidence }
+            });
+        }
+    }
+
+    let (vertical, horizontal) = if pose {
+        (
+            oscillation(&tracker.history, |data| data.pitch, NOD_PITCH * scale),
+            oscillation(&tracker.history, |data| data.yaw, SHAKE_YAW * scale),
+        )
+    } else {
+        (
+            oscillation(&tracker.history, |data| data.center_y, NOD_CENTER_Y * scale),
+            oscillation(
+                &tracker.history,
+                |data| data.center_x,
+                SHAKE_CENTER_X * scale,
+            ),
+        )
+    };
+    // Diagonal movement counts as the stronger one
+    let gesture = match (vertical, horizontal) {
+        (Some(nod), shake) if nod >= shake.unwrap_or(0.0) => {
+            Some(HeadGestureEvent::Nod { confidence: nod })
+        }
+        (_, Some(shake)) => Some(HeadGestureEvent::Shake { confidence: shake }),
+        _ => lean(&tracker.history, LEAN_WIDTH * scale),
+    };
+    if let Some(gesture) = gesture {
+        gestures.send(gesture);
+        // Start over so one movement is not reported again
+        tracker.history.clear();
+    }
+}
+
+// Confidence of back and forth movement, each swing has to reach threshold
+fn oscillation(
+    history: &VecDeque<(Duration, WebcamFacialData)>,
+    value: impl Fn(&WebcamFacialData) -> f32,
+    threshold: f32,
+) -> Option<f32> {
+    let mut values = history.iter().map(|(_, data)| value(data));
+    let mut extreme = values.next()?;
+    let mut direction = 0.0;
+    let mut swings = 0;
+    let mut amplitude = 0.0;
+    for value in values {
+        let change = value - extreme;
+        if direction * change > 0.0 {
+            // Moving further the same way
+            extreme = value;
+        } else if change.abs() >= threshold {
+            if direction != 0.0 {
+                swings += 1;
+            }
+            amplitude += change.abs();
+            direction = change.signum();
+            extreme = value;
+        }
+    }
+    if swings < MIN_SWINGS {
+        return None;
+    }
+    let amplitude = amplitude / (swings + 1) as f32;
+    let repetition = swings.min(FULL_SWINGS) as f32 / FULL_SWINGS as f32;
+    Some(confidence(amplitude, threshold) * repetition)
+}
+
+// Face getting bigger or smaller than at window start
+fn lean(
+    history: &VecDeque<(Duration, WebcamFacialData)>,
+    threshold: f32,
+) -> Option<HeadGestureEvent> {
+    let first = history.front()?.1.width;
+    let last = history.back()?.1.width;
+    if first <= 0.0 {
+        return None;
+    }
+    let change = last / first - 1.0;
+    if change.abs() < threshold {
+        return None;
+    }
+    let confidence = confidence(change.abs(), threshold);
+    Some(if change > 0.0 {
+        HeadGestureEvent::LeanIn { confidence }
+    } else {
+        HeadGestureEvent::LeanOut { confidence }
+    })
+}
+
+// 0.5 at threshold, 1.0 at twice the threshold
+fn confidence(amount: f32, threshold: f32) -> f32 {
+    (amount / threshold / 2.0).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(center_y: &[f32]) -> VecDeque<(Duration, WebcamFacialData)> {
+        center_y
+            .iter()
+            .enumerate()
+            .map(|(index, center_y)| {
+                let data = WebcamFacialData {
+                    center_y: *center_y,
+                    ..Default::default()
+                };
+                (Duration::from_millis(100 * index as u64), data)
+            })
+            .collect()
+    }
+
+    fn nod(center_y: &[f32]) -> Option<f32> {
+        oscillation(&history(center_y), |data| data.center_y, 3.0)
+    }
+
+    #[test]
+    fn one_way_movement_is_not_oscillation() {
+        assert_eq!(nod(&[0.0, 2.0, 5.0, 8.0]), None);
+        assert_eq!(nod(&[]), None);
+    }
+
+    #[test]
+    fn swings_below_threshold_are_ignored() {
+        assert_eq!(nod(&[0.0, 2.0, 0.0, 2.0, 0.0]), None);
+    }
+
+    #[test]
+    fn down_and_back_up_is_one_swing() {
+        // Average amplitude 5.0 over threshold 3.0, one of three swings
+        let confidence = nod(&[0.0, 2.0, 5.0, 3.0, 0.0]).unwrap();
+        assert!((confidence - 5.0 / 6.0 / 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn repeated_swings_raise_confidence() {
+        let once = nod(&[0.0, 6.0, 0.0]).unwrap();
+        let repeated = nod(&[0.0, 6.0, 0.0, 6.0, 0.0, 6.0, 0.0]).unwrap();
+        assert!(repeated > once);
+        assert!((repeated - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,133 @@
+// Mapping of filtered face offsets to game input axes
+use bevy::ecs::{
+    change_detection::DetectChanges,
+    system::{Res, ResMut, Resource},
+};
+
+use crate::{FaceCalibration, WebcamFacialState};
+
+#[derive(Clone, Copy)]
+pub enum ResponseCurve {
+    Linear,
+    Quadratic,
+    Custom(fn(f32) -> f32), // Gets and returns value in range 0.0 .. 1.0
+}
+
+// Settings for one input axis
+#[derive(Clone, Copy)]
+pub struct FaceInputAxis {
+    // Part of range around center (0.0 .. 1.0) where axis stays at zero
+    pub deadzone: f32,
+    pub curve: ResponseCurve,
+    pub gain: f32,
+    // Maximum absolute axis value
+    pub saturation: f32,
+}
+
+impl Default for FaceInputAxis {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.1,
+            curve: ResponseCurve::Linear,
+            gain: 1.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+impl FaceInputAxis {
+    // Maps calibrated face coordinate in range -1.0 .. 1.0 to input axis value
+    pub fn map(&self, value: f32) -> f32 {
+        let normalized = value.clamp(-1.0, 1.0);
+        let magnitude = normalized.abs();
+        if magnitude <= self.deadzone {
+            return 0.0;
+        }
+        // Rescale range outside deadzone back to 0.0 .. 1.0
+        let magnitude = (magnitude - self.deadzone) / (1.0 - self.deadzone);
+        let magnitude = match self.curve {
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Quadratic => magnitude * magnitude,
+            ResponseCurve::Custom(curve) => curve(magnitude),
+        };
+        (normalized.signum() * magnitude * self.gain).clamp(-self.saturation, self.saturation)
+    }
+}
+
+// Mapping settings for face center axes, can be changed at runtime via ResMut<FaceInputMapping>
+#[derive(Resource, Clone, Copy, Default)]
+pub struct FaceInputMapping {
+    pub x: FaceInputAxis,
+    pub y: FaceInputAxis,
+}
+
+// Game input axes computed from filtered and calibrated face center
+#[derive(Resource, Default, Clone, Debug)]
+pub struct FaceInput {
+    pub x: f32,
+    pub y: f32,
+}
+
+pub(crate) fn face_input_mapping_system(
+    mapping: Res<FaceInputMapping>,
+    calibration: Res<FaceCalibration>,
+    facial_state: Res<WebcamFacialState>,
+    mut input: ResMut<FaceInput>,
+) {
+    if !facial_state.is_changed() && !mapping.is_changed() && !calibration.is_changed() {
+        return;
+    }
+    input.x = mapping
+        .x
+        .map(calibration.remap_x(facial_state.filtered.center_x));
+    input.y = mapping
+        .y
+        .map(calibration.remap_y(facial_state.filtered.center_y));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(curve: ResponseCurve, gain: f32, saturation: f32) -> FaceInputAxis {
+        FaceInputAxis {
+            deadzone: 0.2,
+            curve,
+            gain,
+            saturation,
+        }
+    }
+
+    #[test]
+    fn deadzone_maps_to_zero() {
+        let axis = axis(ResponseCurve::Linear, 1.0, 1.0);
+        assert_eq!(axis.map(0.0), 0.0);
+        assert_eq!(axis.map(0.2), 0.0);
+        assert_eq!(axis.map(-0.2), 0.0);
+    }
+
+    #[test]
+    fn range_outside_deadzone_is_rescaled() {
+        let axis = axis(ResponseCurve::Linear, 1.0, 1.0);
+        assert!((axis.map(0.6) - 0.5).abs() < 1e-5);
+        assert!((axis.map(-0.6) + 0.5).abs() < 1e-5);
+        assert!((axis.map(1.0) - 1.0).abs() < 1e-5);
+        assert!((axis.map(-3.0) + 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn curve_is_applied_after_deadzone() {
+        let quadratic = axis(ResponseCurve::Quadratic, 1.0, 1.0);
+        assert!((quadratic.map(-0.6) + 0.25).abs() < 1e-5);
+        let custom = axis(ResponseCurve::Custom(|value| value.sqrt()), 1.0, 1.0);
+        assert!((custom.map(0.4) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn gain_is_limited_by_saturation() {
+        let axis = axis(ResponseCurve::Linear, 2.0, 1.5);
+        assert!((axis.map(0.4) - 0.5).abs() < 1e-5);
+        assert!((axis.map(1.0) - 1.5).abs() < 1e-5);
+        assert!((axis.map(-1.0) + 1.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,91 @@
+// Render-rate interpolation of face data between timestamped detections
+use bevy::{
+    ecs::{
+        event::EventReader,
+        system::{Local, Res, ResMut, Resource},
+    },
+    time::Time,
+};
+use std::time::Duration;
+
+use crate::{WebcamFacialController, WebcamFacialData, WebcamFacialDataEvent};
+
+#[derive(Clone, Copy)]
+pub enum FaceStateInterpolationType {
+    // Plays back between last two detections, delayed by one detection interval
+    Interpolate,
+    // Predicts forward from last two detections, for maximum of given seconds
+    Extrapolate(f32),
+    NoInterpolation,
+}
+
+// Face data interpolated to current frame time, updated every frame
+#[derive(Resource, Default, Clone, Debug)]
+pub struct FaceState {
+    pub data: WebcamFacialData,
+}
+
+// Last two detections with their timestamps
+#[derive(Default)]
+pub(crate) struct FaceStateSamples {
+    previous: Option<(Duration, WebcamFacialData)>,
+    last: Option<(Duration, WebcamFacialData)>,
+}
+
+pub(crate) fn face_state_interpolation_system(
+    webcam_facial: Res<WebcamFacialController>,
+    time: Res<Time>,
+    mut events: EventReader<WebcamFacialDataEvent>,
+    mut samples: Local<FaceStateSamples>,
+    mut state: ResMut<FaceState>,
+) {
+    // Only primary camera of the plugin is interpolated
+    for event in events
+        .iter()
+        .filter(|event| event.2 == webcam_facial.entity)
+    {
+        samples.previous = samples.last.take();
+        samples.last = Some((event.1, event.0.clone()));
+    }
+    let now = time.raw_elapsed().as_secs_f32();
+    state.data = match (&samples.previous, &samples.last) {
+        (Some((t0, d0)), Some((t1, d1))) => {
+            let t0 = t0.as_secs_f32();
+            let t1 = t1.as_secs_f32();
+            let interval = t1 - t0;
+            if interval <= 0.0 {
+                d1.clone()
+            } else {
+                let t = match webcam_facial.config_interpolation_type {
+                    FaceStateInterpolationType::Interpolate => {
+                        ((now - interval - t0) / interval).clamp(0.0, 1.0)
+                    }
+                    FaceStateInterpolationType::Extrapolate(max_seconds) => {
+                        (now.min(t1 + max_seconds) - t0) / interval
+                    }
+                    FaceStateInterpolationType::NoInterpolation => 1.0,
+                };
+                lerp(d0, d1, t)
+            }
+        }
+        (None, Some((_, d1))) => d1.clone(),
+        _ => WebcamFacialData::default(),
+    };
+}
+
+fn lerp(from: &WebcamFacialData, to: &WebcamFacialData, t: f32) -> WebcamFacialData {
+    WebcamFacialData {
+        center_x: from.center_x + (to.center_x - from.center_x) * t,
+        center_y: from.center_y + (to.center_y - from.center_y) * t,
+        x: from.x + (to.x - from.x) * t,
+        y: from.y + (to.y - from.y) * t,
+        width: from.width + (to.width - from.width) * t,
+        height: from.height + (to.height - from.height) * t,
+        score: from.score + (to.score - from.score) * t,
+        yaw: from.yaw + (to.yaw - from.yaw) * t,
+        pitch: from.pitch + (to.pitch - from.pitch) * t,
+        roll: from.roll + (to.roll - from.roll) * t,
+        mouth_open: from.mouth_open + (to.mouth_open - from.mouth_open) * t,
+        smile: from.smile + (to.smile - from.smile) * t,
+    }
+}
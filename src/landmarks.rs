@@ -0,0 +1,166 @@
+// Facial landmarks (eyes, nose, mouth...) found on detected face
+use bevy::math::Vec2;
+
+#[cfg(feature = "onnx")]
+use crate::onnx::PfldLandmarker;
+use crate::{DetectorFrame, FaceDetection};
+
+// Implement to plug another landmark model into webcam task.
+// Returns 5 (eyes, nose, mouth corners) or 68 (iBUG 300-W layout) points in frame pixels.
+pub trait FaceLandmarker {
+    fn landmarks(&mut self, frame: &DetectorFrame, face: &FaceDetection) -> Vec<Vec2>;
+}
+
+#[derive(Clone, Debug)]
+pub enum FaceLandmarksType {
+    // PFLD 68 point ONNX model run on square crop around face
+    #[cfg(feature = "onnx")]
+    Pfld {
+        model_path: String,
+    },
+    // Landmarker created by function in webcam task every time it starts
+    Custom(fn() -> Result<Box<dyn FaceLandmarker>, String>),
+    NoLandmarks,
+}
+
+impl FaceLandmarksType {
+    pub(crate) fn create(&self) -> Result<Option<Box<dyn FaceLandmarker>>, String> {
+        match self {
+            #[cfg(feature = "onnx")]
+            FaceLandmarksType::Pfld { model_path } => match PfldLandmarker::new(model_path) {
+                Ok(landmarker) => Ok(Some(Box::new(landmarker))),
+                Err(error) => Err(error.to_string()),
+            },
+            FaceLandmarksType::Custom(create) => create().map(Some),
+            FaceLandmarksType::NoLandmarks => Ok(None),
+        }
+    }
+}
+
+// Landmarks of selected face in same -50.0 .. 50.0 units as face rectangle x/y,
+// x grows to the right and y downwards in camera frame (not mirrored as center_x)
+#[derive(Default, Clone, Debug)]
+pub struct FaceLandmarks {
+    pub points: Vec<Vec2>,
+}
+
+impl FaceLandmarks {
+    // Frame pixels to face units, y is scaled by frame width as well
+    pub(crate) fn from_pixels(points: Vec<Vec2>, camera_width: u32) -> Self {
+        let scale = 100.0 / camera_width as f32;
+        Self {
+            points: points
+                .into_iter()
+                .map(|point| point * scale - Vec2::splat(50.0))
+                .collect(),
+        }
+    }
+
+    // Eye on the left side of camera frame (player's right eye)
+    pub fn left_eye(&self) -> Option<Vec2> {
+        match self.points.len() {
+            5 => Some(self.points[0]),
+            68 => Some(self.mean(36..42)),
+            _ => None,
+        }
+    }
+
+    // Eye on the right side of camera frame (player's left eye)
+    pub fn right_eye(&self) -> Option<Vec2> {
+        match self.points.len() {
+            5 => Some(self.points[1]),
+            68 => Some(self.mean(42..48)),
+            _ => None,
+        }
+    }
+
+    pub fn nose(&self) -> Option<Vec2> {
+        match self.points.len() {
+            5 => Some(self.points[2]),
+            68 => Some(self.points[30]),
+            _ => None,
+        }
+    }
+
+    pub fn mouth_left(&self) -> Option<Vec2> {
+        match self.points.len() {
+            5 => Some(self.points[3]),
+            68 => Some(self.points[48]),
+            _ => None,
+        }
+    }
+
+    pub fn mouth_right(&self) -> Option<Vec2> {
+        match self.points.len() {
+            5 => Some(self.points[4]),
+            68 => Some(self.points[54]),
+            _ => None,
+        }
+    }
+
+    // Eye aspect ratio (eye height to width) of eye on the left side of camera frame,
+    // about 0.3 when open and close to 0.0 when closed. Needs 68 points.
+    pub fn left_eye_aspect_ratio(&self) -> Option<f32> {
+        self.eye_aspect_ratio(36)
+    }
+
+    // Eye aspect ratio of eye on the right side of camera frame
+    pub fn right_eye_aspect_ratio(&self) -> Option<f32> {
+        self.eye_aspect_ratio(42)
+    }
+
+    // Inner lips height to mouth width, close to 0.0 when closed and about 0.5 wide open.
+    // Needs 68 points.
+    pub fn mouth_openness(&self) -> Option<f32> {
+        if self.points.len() != 68 {
+            return None;
+        }
+        let width = self.points[60].distance(self.points[64]);
+        if width <= 0.0 {
+            return None;
+        }
+        let height = (self.points[61].distance(self.points[67])
+            + self.points[62].distance(self.points[66])
+            + self.points[63].distance(self.points[65]))
+            / 3.0;
+        Some(height / width)
+    }
+
+    // Smile score 0.0 .. 1.0 from mouth width relative to eyes and lifted mouth corners.
+    // Needs 68 points.
+    pub fn smile(&self) -> Option<f32> {
+        if self.points.len() != 68 {
+            return None;
+        }
+        let eyes_width = self.points[36].distance(self.points[45]);
+        let mouth_width = self.points[48].distance(self.points[54]);
+        if eyes_width <= 0.0 || mouth_width <= 0.0 {
+            return None;
+        }
+        // Neutral mouth is a bit less than half as wide as outer eye corners apart
+        let widening = ((mouth_width / eyes_width - 0.45) / 0.15).clamp(0.0, 1.0);
+        // Corners above middle of lips, y grows downwards
+        let lips_middle = (self.points[51].y + self.points[57].y) / 2.0;
+        let corners = (self.points[48].y + self.points[54].y) / 2.0;
+        let lift = ((lips_middle - corners) / mouth_width / 0.1).clamp(0.0, 1.0);
+        Some((widening + lift) / 2.0)
+    }
+
+    // Six eye contour points starting from outer corner
+    fn eye_aspect_ratio(&self, first: usize) -> Option<f32> {
+        if self.points.len() != 68 {
+            return None;
+        }
+        let eye = &self.points[first..first + 6];
+        let width = eye[0].distance(eye[3]);
+        if width <= 0.0 {
+            return None;
+        }
+        Some((eye[1].distance(eye[5]) + eye[2].distance(eye[4])) / (2.0 * width))
+    }
+
+    fn mean(&self, range: std::ops::Range<usize>) -> Vec2 {
+        let count = range.len() as f32;
+        self.points[range].iter().copied().sum::<Vec2>() / count
+    }
+}
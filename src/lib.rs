@@ -3,33 +3,139 @@
 
 use bevy::{
     app::{App, Plugin, Update},
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic},
     ecs::{
         component::Component,
         entity::Entity,
-        event::{Event, EventWriter},
-        system::{Commands, Query, ResMut, Resource},
+        event::Event,
+        schedule::{BoxedScheduleLabel, IntoSystemConfigs, IntoSystemSetConfigs, SystemSet},
+        system::{Res, Resource},
     },
     log::{debug, error, info},
-    tasks::{AsyncComputeTaskPool, Task},
+    math::Vec2,
 };
 
 use crossbeam_channel::{bounded, Receiver, SendError, Sender};
-use futures_lite::future;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-// camera capture
-use camera_capture;
-// rustface detector
-use rustface::ImageData;
 // image utils
 use image::{ImageBuffer, Luma};
+// Frame sources, webcam or custom
+mod source;
+pub use source::{FrameSource, FrameSourceType};
+// Face detector backends
+mod detector;
+pub use detector::{DetectorFrame, FaceDetection, FaceDetector, FaceDetectorType};
+// Facial landmarks
+mod landmarks;
+pub use landmarks::{FaceLandmarker, FaceLandmarks, FaceLandmarksType};
+// Head pose from landmarks
+mod pose;
+use pose::head_pose;
+// Blink and wink detection
+mod blink;
+use blink::face_blink_system;
+pub use blink::{BlinkDetectionType, BlinkEvent, Eye, FaceEyes, WinkEvent};
+// Mouth open and smile threshold events
+mod expression;
+use expression::face_expression_system;
+pub use expression::{ExpressionDetectionType, MouthOpenEvent, SmileEvent};
+// Head gesture recognition
+mod gesture;
+use gesture::face_gesture_system;
+pub use gesture::{HeadGestureEvent, HeadGestureType};
+// Emotion classification
+#[cfg(feature = "emotion")]
+mod emotion;
+#[cfg(feature = "emotion")]
+pub use emotion::{Emotion, EmotionClassifier, EmotionClassifierType, FaceEmotions};
+// ONNX detector models
+#[cfg(feature = "onnx")]
+mod onnx;
 // Data filter/smoothing
 mod filter;
-use filter::WebcamFacialDataFiltered;
 pub use filter::SmoothingFilterType;
+use filter::WebcamFacialDataFiltered;
+// Outlier/jump rejection before smoothing
+mod gate;
+pub use gate::OutlierGateType;
+use gate::WebcamFacialDataGate;
+// Render-rate interpolation of face data
+mod interpolation;
+use interpolation::face_state_interpolation_system;
+pub use interpolation::{FaceState, FaceStateInterpolationType};
+// Latest face data as resource
+mod state;
+pub use state::WebcamFacialState;
+// Game input mapping
+mod input;
+use input::face_input_mapping_system;
+pub use input::{FaceInput, FaceInputAxis, FaceInputMapping, ResponseCurve};
+// Player calibration
+mod calibration;
+use calibration::face_calibration_system;
+pub use calibration::{
+    FaceCalibration, FaceCalibrationRequest, FaceCalibrationStep, FaceCalibrationStepDone,
+};
+// Distance estimation from face size
+mod depth;
+pub use depth::FaceDepth;
+use depth::{face_depth_system, frame_center_y};
+// Transforms driven by face data
+mod follower;
+use follower::face_driven_system;
+pub use follower::{FaceDriven, FaceMapping, FaceSource, FaceTarget};
+// Live webcam preview texture
+mod preview;
+#[cfg(feature = "textures")]
+use preview::webcam_preview_system;
+use preview::{preview_frame, WebcamPreviewFrameBuffer};
+#[cfg(feature = "textures")]
+pub use preview::{WebcamFacialPreview, WEBCAM_PREVIEW_IMAGE_HANDLE};
+pub use preview::{WebcamPreviewFrame, WebcamPreviewType};
+// Face crop texture
+mod crop;
+#[cfg(feature = "textures")]
+use crop::face_crop_system;
+pub use crop::FaceCropType;
+use crop::{face_crop_frame, FaceCropFrameBuffer};
+#[cfg(feature = "textures")]
+pub use crop::{
+    CaptureFaceCrop, FaceCropCaptured, WebcamFacialFaceCrop, WEBCAM_FACE_CROP_IMAGE_HANDLE,
+};
+// Snapshot capture
+mod snapshot;
+use snapshot::{snapshot_system, take_snapshot, SnapshotFrameBuffer, SnapshotSaveTasks};
+pub use snapshot::{CaptureSnapshot, SnapshotCaptured, WebcamSnapshot};
+// Debug overlay with gizmos
+#[cfg(feature = "debug")]
+mod debug;
+#[cfg(feature = "debug")]
+pub use debug::{WebcamFacialDebug, WebcamFacialDebugPlugin};
+// Start/stop from app States and window focus
+mod activity;
+use activity::window_focus_system;
+pub use activity::{WebcamFacialStatesPlugin, WindowFocusPolicy};
+// Additional webcams as entities
+mod webcam;
+pub use webcam::WebcamFacialCamera;
+use webcam::{webcam_facial_camera_runner, WebcamFacialCameraChannel};
+// Stereo triangulation from two cameras
+mod stereo;
+use stereo::face_stereo_system;
+pub use stereo::{StereoHeadPosition, WebcamFacialStereo};
+// Head-coupled perspective camera
+#[cfg(feature = "head_coupled")]
+mod perspective;
+#[cfg(feature = "head_coupled")]
+pub use perspective::{HeadCoupledCameraPlugin, HeadCoupledProjection};
 
 pub struct WebcamFacialPlugin {
     pub config_webcam_device: u32,
@@ -37,30 +143,134 @@ pub struct WebcamFacialPlugin {
     pub config_webcam_height: u32,
     pub config_webcam_framerate: u32,
     pub config_webcam_autostart: bool,
+    pub config_frame_source: FrameSourceType,
+    pub config_detector_type: FaceDetectorType,
+    pub config_landmarks_type: FaceLandmarksType,
+    pub config_blink_type: BlinkDetectionType,
+    pub config_expression_type: ExpressionDetectionType,
+    pub config_gesture_type: HeadGestureType,
+    #[cfg(feature = "emotion")]
+    pub config_emotion_type: EmotionClassifierType,
+    #[cfg(feature = "emotion")]
+    pub config_emotion_alpha: f32,
     pub config_filter_type: SmoothingFilterType,
+    pub config_filter_size_type: SmoothingFilterType,
+    pub config_filter_score_type: SmoothingFilterType,
     pub config_filter_length: u32,
+    pub config_gate_type: OutlierGateType,
+    pub config_interpolation_type: FaceStateInterpolationType,
+    pub config_input_mapping: FaceInputMapping,
+    pub config_camera_fov: f32,
+    pub config_face_width: f32,
+    pub config_preview_type: WebcamPreviewType,
+    pub config_preview_downscale: u32,
+    pub config_face_crop_type: FaceCropType,
+    pub config_face_crop_size: u32,
+    pub config_face_crop_padding: f32,
+    // Schedule of plugin systems. HeadCoupledCameraPlugin and WebcamFacialDebugPlugin always
+    // run every frame in Update and are ordered after WebcamFacialSet::Process only when this is
+    // Update too. FaceState interpolation runs in this schedule, so it is per frame only in
+    // Update or PreUpdate.
+    pub config_schedule: BoxedScheduleLabel,
+    pub config_focus_policy: WindowFocusPolicy,
 }
 // Plugin configuration for webcam to be accesible from plugin system
 #[derive(Resource)]
 pub struct WebcamFacialController {
-    pub sender: Sender<WebcamFacialData>,
-    pub receiver: Receiver<WebcamFacialData>,
+    pub sender: Sender<WebcamFacialTaskData>,
+    pub receiver: Receiver<WebcamFacialTaskData>,
     pub control: bool,
+    // Primary camera WebcamFacialCamera entity configured in plugin
+    pub entity: Entity,
+    // Set by window focus policy, capture runs only when control is on and not paused
+    pub paused: bool,
     pub status: Arc<AtomicBool>,
-    config_device: u32,
+    rejected_frames: Arc<AtomicU32>,
+    face_crop_requested: Arc<AtomicBool>,
+    snapshot_sender: Sender<CaptureSnapshot>,
+    snapshot_receiver: Receiver<CaptureSnapshot>,
     config_width: u32,
     config_height: u32,
-    config_framerate: u32,
-    config_filter_type: SmoothingFilterType,
-    config_filter_length: u32,
+    config_blink_type: BlinkDetectionType,
+    config_expression_type: ExpressionDetectionType,
+    config_gesture_type: HeadGestureType,
+    #[cfg(feature = "emotion")]
+    config_emotion_type: EmotionClassifierType,
+    #[cfg(feature = "emotion")]
+    config_emotion_alpha: f32,
+    config_interpolation_type: FaceStateInterpolationType,
+    config_camera_fov: f32,
+    config_face_width: f32,
+    config_preview_type: WebcamPreviewType,
+    config_preview_downscale: u32,
+    config_face_crop_type: FaceCropType,
+    config_face_crop_size: u32,
+    config_face_crop_padding: f32,
+    config_focus_policy: WindowFocusPolicy,
+}
+
+// Sets of plugin systems, order your systems after them to get data in the same frame
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WebcamFacialSet {
+    // Task data is received, WebcamFacialState is updated and events are sent
+    Receive,
+    // Calibration, input, depth, interpolation, textures and FaceDriven Transforms are updated
+    Process,
 }
 
+// Capture loop blocks on camera, so each one runs on own thread instead of task pool
 #[derive(Component)]
-struct WebcamFacialTask(Task<bool>);
+pub(crate) struct WebcamFacialTask(Option<JoinHandle<WebcamFacialTaskExit>>);
+
+// Why capture thread finished, camera is disabled unless it was stopped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WebcamFacialTaskExit {
+    // Status flag was cleared or data receiver dropped
+    Stopped,
+    // Frame source ran out of frames
+    SourceEnded,
+    // Setup failed, frame didn't match camera resolution or thread panicked
+    Failed,
+}
+
+impl WebcamFacialTask {
+    pub(crate) fn new(handle: JoinHandle<WebcamFacialTaskExit>) -> Self {
+        Self(Some(handle))
+    }
+
+    // Result once thread finished
+    pub(crate) fn finished(&mut self) -> Option<WebcamFacialTaskExit> {
+        if !self.0.as_ref()?.is_finished() {
+            return None;
+        }
+        Some(
+            self.0
+                .take()?
+                .join()
+                .unwrap_or(WebcamFacialTaskExit::Failed),
+        )
+    }
+}
 
 // WebcamFacialEvent event for sending WebcamFacialData to main Bevy app
+// together with frame capture time since app startup and source camera entity
 #[derive(Event)]
-pub struct WebcamFacialDataEvent(pub WebcamFacialData);
+pub struct WebcamFacialDataEvent(pub WebcamFacialData, pub Duration, pub Entity);
+
+// Data sent from webcam task for every processed frame
+#[derive(Clone, Debug)]
+pub struct WebcamFacialTaskData {
+    pub frame_time: Instant,
+    pub raw: WebcamFacialData,
+    pub filtered: WebcamFacialData,
+    pub faces: Vec<WebcamFacialData>,
+    pub landmarks: FaceLandmarks,
+    #[cfg(feature = "emotion")]
+    pub emotions: FaceEmotions,
+    pub preview: Option<WebcamPreviewFrame>,
+    pub face_crop: Option<WebcamPreviewFrame>,
+    pub snapshot: Option<WebcamSnapshot>,
+}
 
 // Data structure to be exchanged with Bevy
 #[derive(Default, Clone, Debug)]
@@ -72,31 +282,226 @@ pub struct WebcamFacialData {
     pub width: f32,
     pub height: f32,
     pub score: f32,
+    // Head rotation in degrees, zero without landmarks
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    // Mouth openness ratio and smile score 0.0 .. 1.0, zero without 68 point landmarks
+    pub mouth_open: f32,
+    pub smile: f32,
 }
 
 impl Plugin for WebcamFacialPlugin {
     fn build(&self, app: &mut App) {
         // Add thread channels for data exchange
         let (task_channel_sender, task_channel_receiver) = bounded(1);
-        let task_status = Arc::new(AtomicBool::new(false));
+        let (snapshot_sender, snapshot_receiver) = bounded(1);
+        // Primary camera is entity like other cameras, sharing channel and flags with controller
+        let camera = self.camera();
+        let entity = app
+            .world
+            .spawn((
+                WebcamFacialCameraChannel::new(
+                    task_channel_sender.clone(),
+                    task_channel_receiver.clone(),
+                ),
+                WebcamFacialState::default(),
+            ))
+            .id();
         // Store plugin control,data channels and settings in a resource
         let plugin = WebcamFacialController {
             sender: task_channel_sender,
             receiver: task_channel_receiver,
-            control: self.config_webcam_autostart.clone(),
-            status: task_status,
-
-            config_device: self.config_webcam_device.clone(),
-            config_width: self.config_webcam_width.clone(),
-            config_height: self.config_webcam_height.clone(),
-            config_framerate: self.config_webcam_framerate.clone(),
-            config_filter_type: self.config_filter_type.clone(),
-            config_filter_length: self.config_filter_length.clone(),
+            control: self.config_webcam_autostart,
+            entity,
+            paused: false,
+            status: camera.status.clone(),
+            rejected_frames: camera.rejected_frames.clone(),
+            face_crop_requested: Arc::new(AtomicBool::new(false)),
+            snapshot_sender,
+            snapshot_receiver,
+
+            config_width: self.config_webcam_width,
+            config_height: self.config_webcam_height,
+            config_blink_type: self.config_blink_type,
+            config_expression_type: self.config_expression_type,
+            config_gesture_type: self.config_gesture_type,
+            #[cfg(feature = "emotion")]
+            config_emotion_type: self.config_emotion_type.clone(),
+            #[cfg(feature = "emotion")]
+            config_emotion_alpha: self.config_emotion_alpha,
+            config_interpolation_type: self.config_interpolation_type,
+            config_camera_fov: self.config_camera_fov,
+            config_face_width: self.config_face_width,
+            // Frames are not prepared in task when there is no Image to put them into
+            config_preview_type: if cfg!(feature = "textures") {
+                self.config_preview_type
+            } else {
+                WebcamPreviewType::NoPreview
+            },
+            config_preview_downscale: self.config_preview_downscale,
+            config_face_crop_type: if cfg!(feature = "textures") {
+                self.config_face_crop_type
+            } else {
+                FaceCropType::NoCrop
+            },
+            config_face_crop_size: self.config_face_crop_size,
+            config_face_crop_padding: self.config_face_crop_padding,
+            config_focus_policy: self.config_focus_policy,
         };
+        app.world.entity_mut(entity).insert(camera);
+        // Calibration inserted before plugin (e.g. loaded player calibration) is kept
+        if !app.world.contains_resource::<FaceCalibration>() {
+            app.insert_resource(FaceCalibration::uncalibrated(
+                self.config_webcam_height as f32 / self.config_webcam_width as f32,
+            ));
+        }
         // Insert nesecary resources, events and systems
         app.insert_resource(plugin)
+            .insert_resource(self.config_input_mapping)
+            .init_resource::<WebcamFacialState>()
+            .init_resource::<FaceInput>()
+            .init_resource::<FaceDepth>()
+            .init_resource::<WebcamPreviewFrameBuffer>()
+            .init_resource::<FaceCropFrameBuffer>()
+            .init_resource::<SnapshotFrameBuffer>()
+            .init_resource::<SnapshotSaveTasks>()
             .add_event::<WebcamFacialDataEvent>()
-            .add_systems(Update, webcam_facial_task_runner);
+            .add_event::<FaceCalibrationRequest>()
+            .add_event::<FaceCalibrationStepDone>()
+            .add_event::<CaptureSnapshot>()
+            .add_event::<SnapshotCaptured>()
+            .register_diagnostic(Diagnostic::new(
+                Self::REJECTED_FRAMES,
+                "webcam_facial_rejected_frames",
+                20,
+            ))
+            .configure_sets(
+                self.config_schedule.clone(),
+                (WebcamFacialSet::Receive, WebcamFacialSet::Process).chain(),
+            )
+            .add_systems(
+                self.config_schedule.clone(),
+                (window_focus_system, webcam_facial_camera_runner)
+                    .chain()
+                    .in_set(WebcamFacialSet::Receive),
+            )
+            .add_systems(
+                self.config_schedule.clone(),
+                (
+                    webcam_facial_diagnostic_system,
+                    snapshot_system,
+                    face_calibration_system,
+                    face_input_mapping_system.after(face_calibration_system),
+                    face_depth_system.after(face_calibration_system),
+                    face_stereo_system,
+                    face_driven_system
+                        .after(face_input_mapping_system)
+                        .after(face_depth_system)
+                        .after(face_state_interpolation_system),
+                )
+                    .in_set(WebcamFacialSet::Process),
+            );
+        // Preview texture is optional, it needs `textures` feature and Assets<Image>
+        // from bevy render plugins
+        #[cfg(feature = "textures")]
+        match self.config_preview_type {
+            WebcamPreviewType::NoPreview => {}
+            _ => {
+                app.init_resource::<WebcamFacialPreview>().add_systems(
+                    self.config_schedule.clone(),
+                    webcam_preview_system.in_set(WebcamFacialSet::Process),
+                );
+            }
+        }
+        // Face crop texture is optional, it needs `textures` feature and Assets<Image>
+        // from bevy render plugins
+        #[cfg(feature = "textures")]
+        match self.config_face_crop_type {
+            FaceCropType::NoCrop => {}
+            _ => {
+                app.init_resource::<WebcamFacialFaceCrop>()
+                    .add_event::<CaptureFaceCrop>()
+                    .add_event::<FaceCropCaptured>()
+                    .add_systems(
+                        self.config_schedule.clone(),
+                        face_crop_system.in_set(WebcamFacialSet::Process),
+                    );
+            }
+        }
+        // Blink detection is optional, it needs 68 point landmarks
+        match self.config_blink_type {
+            BlinkDetectionType::NoBlinkDetection => {}
+            _ => {
+                app.init_resource::<FaceEyes>()
+                    .add_event::<BlinkEvent>()
+                    .add_event::<WinkEvent>()
+                    .add_systems(
+                        self.config_schedule.clone(),
+                        face_blink_system.in_set(WebcamFacialSet::Process),
+                    );
+            }
+        }
+        // Mouth open and smile events are optional, they need 68 point landmarks
+        match self.config_expression_type {
+            ExpressionDetectionType::NoExpressionEvents => {}
+            _ => {
+                app.add_event::<MouthOpenEvent>()
+                    .add_event::<SmileEvent>()
+                    .add_systems(
+                        self.config_schedule.clone(),
+                        face_expression_system.in_set(WebcamFacialSet::Process),
+                    );
+            }
+        }
+        // Head gestures are optional, tilts and pose based nods need landmarks
+        match self.config_gesture_type {
+            HeadGestureType::NoGestures => {}
+            _ => {
+                app.add_event::<HeadGestureEvent>().add_systems(
+                    self.config_schedule.clone(),
+                    face_gesture_system.in_set(WebcamFacialSet::Process),
+                );
+            }
+        }
+        // Interpolated face state is optional
+        match self.config_interpolation_type {
+            FaceStateInterpolationType::NoInterpolation => {}
+            _ => {
+                app.init_resource::<FaceState>().add_systems(
+                    self.config_schedule.clone(),
+                    face_state_interpolation_system.in_set(WebcamFacialSet::Process),
+                );
+            }
+        }
+    }
+}
+
+impl WebcamFacialPlugin {
+    // Diagnostic with total count of frames rejected by outlier gate
+    pub const REJECTED_FRAMES: DiagnosticId =
+        DiagnosticId::from_u128(169435826178313724629361053818462701543);
+
+    // Primary camera from plugin config, also defaults of WebcamFacialCamera
+    pub(crate) fn camera(&self) -> WebcamFacialCamera {
+        WebcamFacialCamera {
+            device: self.config_webcam_device,
+            width: self.config_webcam_width,
+            height: self.config_webcam_height,
+            framerate: self.config_webcam_framerate,
+            frame_source: self.config_frame_source.clone(),
+            detector_type: self.config_detector_type.clone(),
+            landmarks_type: self.config_landmarks_type.clone(),
+            camera_fov: self.config_camera_fov,
+            control: self.config_webcam_autostart,
+            filter_type: self.config_filter_type,
+            filter_size_type: self.config_filter_size_type,
+            filter_score_type: self.config_filter_score_type,
+            filter_length: self.config_filter_length,
+            gate_type: self.config_gate_type,
+            status: Arc::new(AtomicBool::new(false)),
+            rejected_frames: Arc::new(AtomicU32::new(0)),
+        }
     }
 }
 
@@ -108,210 +513,367 @@ impl Default for WebcamFacialPlugin {
             config_webcam_height: 480,
             config_webcam_framerate: 15,
             config_webcam_autostart: true,
+            config_frame_source: FrameSourceType::Webcam,
+            config_detector_type: FaceDetectorType::default(),
+            config_landmarks_type: FaceLandmarksType::NoLandmarks,
+            config_blink_type: BlinkDetectionType::NoBlinkDetection,
+            config_expression_type: ExpressionDetectionType::NoExpressionEvents,
+            config_gesture_type: HeadGestureType::NoGestures,
+            #[cfg(feature = "emotion")]
+            config_emotion_type: EmotionClassifierType::NoEmotions,
+            #[cfg(feature = "emotion")]
+            config_emotion_alpha: 0.3,
             config_filter_type: SmoothingFilterType::LowPass(0.1),
+            config_filter_size_type: SmoothingFilterType::LowPass(0.1),
+            config_filter_score_type: SmoothingFilterType::LowPass(0.1),
             config_filter_length: 10,
+            config_gate_type: OutlierGateType::Threshold {
+                position: 20.0,
+                size: 15.0,
+                frames: 3,
+            },
+            config_interpolation_type: FaceStateInterpolationType::NoInterpolation,
+            config_input_mapping: FaceInputMapping::default(),
+            config_camera_fov: 60.0,
+            config_face_width: 0.15,
+            config_preview_type: WebcamPreviewType::NoPreview,
+            config_preview_downscale: 2,
+            config_face_crop_type: FaceCropType::NoCrop,
+            config_face_crop_size: 128,
+            config_face_crop_padding: 0.25,
+            config_schedule: Box::new(Update),
+            config_focus_policy: WindowFocusPolicy::Ignore,
         }
     }
 }
 
-fn webcam_facial_task_runner(
-    mut webcam_facial: ResMut<WebcamFacialController>,
-    mut commands: Commands,
-    mut plugin_task: Query<(Entity, &mut WebcamFacialTask)>,
-    mut plugin_events: EventWriter<WebcamFacialDataEvent>,
-) {
-    // If enabled and not running - start task
-    if webcam_facial.control & !webcam_facial.status.load(Ordering::SeqCst) {
-        // Get Arc clones
-        let task_running = webcam_facial.status.clone();
-        let sender_clone = webcam_facial.sender.clone();
-
-        let camera_device = webcam_facial.config_device;
-        let camera_width = webcam_facial.config_width;
-        let camera_height = webcam_facial.config_height;
-        let camera_framerate = webcam_facial.config_framerate;
-        let filter_type = webcam_facial.config_filter_type;
-        let filter_length = webcam_facial.config_filter_length;
-
-        info!("Starting plugin");
-        let thread_pool = AsyncComputeTaskPool::get();
-        // Main task and its loop
-        let task = thread_pool.spawn(async move {
-            // Initialize webcam
-            let mut cam_iter = match get_camera_frame_iterator(
-                camera_device,
+// Settings of webcam task, taken from plugin config or camera entity
+#[derive(Clone)]
+pub(crate) struct WebcamFacialTaskConfig {
+    pub device: u32,
+    pub width: u32,
+    pub height: u32,
+    pub framerate: u32,
+    pub frame_source: FrameSourceType,
+    pub detector_type: FaceDetectorType,
+    pub landmarks_type: FaceLandmarksType,
+    #[cfg(feature = "emotion")]
+    pub emotion_type: EmotionClassifierType,
+    #[cfg(feature = "emotion")]
+    pub emotion_alpha: f32,
+    pub camera_fov: f32,
+    pub filter_type: SmoothingFilterType,
+    pub filter_size_type: SmoothingFilterType,
+    pub filter_score_type: SmoothingFilterType,
+    pub filter_length: u32,
+    pub gate_type: OutlierGateType,
+    pub preview_type: WebcamPreviewType,
+    pub preview_downscale: u32,
+    pub face_crop_type: FaceCropType,
+    pub face_crop_size: u32,
+    pub face_crop_padding: f32,
+}
+
+// Channels and flags shared between webcam task and Bevy systems
+pub(crate) struct WebcamFacialTaskShared {
+    pub sender: Sender<WebcamFacialTaskData>,
+    pub status: Arc<AtomicBool>,
+    pub rejected_frames: Arc<AtomicU32>,
+    pub face_crop_requested: Arc<AtomicBool>,
+    pub snapshot_receiver: Receiver<CaptureSnapshot>,
+}
+
+// Spawns thread capturing frames and detecting faces until status flag is cleared
+pub(crate) fn spawn_webcam_facial_task(
+    config: WebcamFacialTaskConfig,
+    shared: WebcamFacialTaskShared,
+) -> JoinHandle<WebcamFacialTaskExit> {
+    let task_running = shared.status;
+    let sender_clone = shared.sender;
+    let rejected_frames = shared.rejected_frames;
+    let face_crop_requested = shared.face_crop_requested;
+    let snapshot_receiver = shared.snapshot_receiver;
+
+    let camera_device = config.device;
+    let camera_width = config.width;
+    let camera_height = config.height;
+    let camera_framerate = config.framerate;
+    let frame_source = config.frame_source;
+    let detector_type = config.detector_type;
+    let landmarks_type = config.landmarks_type;
+    #[cfg(feature = "emotion")]
+    let emotion_type = config.emotion_type;
+    #[cfg(feature = "emotion")]
+    let emotion_alpha = config.emotion_alpha;
+    // Camera projection in face units for head pose
+    let principal_point = Vec2::new(
+        0.0,
+        frame_center_y(camera_height as f32 / camera_width as f32),
+    );
+    let focal_length = 50.0 / (config.camera_fov.to_radians() / 2.0).tan();
+    let filter_type = config.filter_type;
+    let filter_size_type = config.filter_size_type;
+    let filter_score_type = config.filter_score_type;
+    let filter_length = config.filter_length;
+    let gate_type = config.gate_type;
+    let preview_type = config.preview_type;
+    let preview_downscale = config.preview_downscale;
+    let face_crop_type = config.face_crop_type;
+    let face_crop_size = config.face_crop_size;
+    let face_crop_padding = config.face_crop_padding;
+
+    info!("Starting plugin");
+    // Main task and its loop
+    thread::spawn(move || {
+        // Initialize webcam or custom frame source
+        let mut frames =
+            match frame_source.create(camera_device, camera_width, camera_height, camera_framerate)
+            {
+                Ok(frames) => frames,
+                Err(error) => {
+                    error!("Failed to create frame source: {}", error);
+                    return WebcamFacialTaskExit::Failed;
+                }
+            };
+        // Initialize face detector
+        let mut detector = match detector_type.create() {
+            Ok(detector) => detector,
+            Err(error) => {
+                error!("Failed to create detector: {}", error);
+                return WebcamFacialTaskExit::Failed;
+            }
+        };
+        // Initialize optional landmark model
+        let mut landmarker = match landmarks_type.create() {
+            Ok(landmarker) => landmarker,
+            Err(error) => {
+                error!("Failed to create landmarker: {}", error);
+                return WebcamFacialTaskExit::Failed;
+            }
+        };
+        // Initialize optional emotion model
+        #[cfg(feature = "emotion")]
+        let mut emotion_classifier = match emotion_type.create() {
+            Ok(classifier) => classifier,
+            Err(error) => {
+                error!("Failed to create emotion classifier: {}", error);
+                return WebcamFacialTaskExit::Failed;
+            }
+        };
+        #[cfg(feature = "emotion")]
+        let mut emotions: Option<FaceEmotions> = None;
+
+        let mut filtered_data = WebcamFacialDataFiltered::new(
+            filter_length,
+            filter_type,
+            filter_size_type,
+            filter_score_type,
+        );
+        let mut gate = WebcamFacialDataGate::new(gate_type);
+        info!("Capturing frames...");
+        while task_running.load(Ordering::SeqCst) {
+            // Get frame from buffer, source running out of frames ends capture
+            let rgb_frame = match frames.next_frame() {
+                Some(frame) => frame,
+                None => {
+                    info!("Frame source ended.");
+                    return WebcamFacialTaskExit::SourceEnded;
+                }
+            };
+            if rgb_frame.dimensions() != (camera_width, camera_height) {
+                error!(
+                    "Frame size {:?} differs from camera resolution {}x{}.",
+                    rgb_frame.dimensions(),
+                    camera_width,
+                    camera_height
+                );
+                return WebcamFacialTaskExit::Failed;
+            }
+            let frame_time = Instant::now();
+            // RGB pixel reader shared by grayscale, preview, crop and snapshot
+            let rgb_pixel = |x, y| rgb_frame.get_pixel(x, y).0;
+            // Convert RGB frame to grayscale
+            let grayscale_image = ImageBuffer::from_fn(camera_width, camera_height, |x, y| {
+                let [r, g, b] = rgb_pixel(x, y);
+                let gray_value = r as u32 * 77 + g as u32 * 150 + b as u32 * 29;
+                Luma([((gray_value >> 8) & 0xFF) as u8])
+            });
+            // Downscaled copy of frame for preview texture
+            let preview = preview_frame(
                 camera_width,
                 camera_height,
-                camera_framerate,
-            ) {
-                Some(iter) => iter,
+                preview_type,
+                preview_downscale,
+                rgb_pixel,
+            );
+            let detector_frame = DetectorFrame {
+                width: camera_width,
+                height: camera_height,
+                rgb: rgb_frame.as_raw(),
+                grayscale: grayscale_image.as_raw(),
+            };
+            // Detect face data in provided frame
+            let faces = detector.detect(&detector_frame);
+
+            // Initialize zero values if face not found
+            let mut facial_data = WebcamFacialData::default();
+
+            // Get face with maximum human face probability (best candidate)
+            let max_face = faces.iter().max_by(|a, b| a.score.total_cmp(&b.score));
+            match max_face {
+                Some(max_face) => {
+                    debug!("Max score face: {:?}", max_face);
+                    facial_data = facial_data_from_face(max_face, camera_width);
+                }
                 None => {
-                    return false;
+                    debug!("No faces found. Using default zero values.");
                 }
+            }
+            // Landmarks of selected face
+            let landmarks = match (landmarker.as_mut(), max_face) {
+                (Some(landmarker), Some(max_face)) => FaceLandmarks::from_pixels(
+                    landmarker.landmarks(&detector_frame, max_face),
+                    camera_width,
+                ),
+                _ => FaceLandmarks::default(),
             };
-            // Initialize face detector
-            //TODO Model selection, remove hardcoded
-            let mut detector =
-                match rustface::create_detector(&"assets/NN_Models/seeta.bin".to_string()) {
-                    Ok(mut detector) => {
-                        info!("Using assets/NN_Models/seeta.bin recognition model.");
-                        detector.set_min_face_size(20);
-                        detector.set_score_thresh(2.0);
-                        detector.set_pyramid_scale_factor(0.8);
-                        detector.set_slide_window_step(4, 4);
-                        detector
-                    }
-                    Err(error) => {
-                        error!("Failed to create detector: {}", error.to_string());
-                        return false;
+            // Emotions of selected face, first result is taken as it is
+            #[cfg(feature = "emotion")]
+            {
+                let classified = match (emotion_classifier.as_mut(), max_face) {
+                    (Some(classifier), Some(max_face)) => {
+                        classifier.classify(&detector_frame, max_face)
                     }
+                    _ => None,
                 };
-
-            let mut filtered_data = WebcamFacialDataFiltered::new(filter_length, filter_type);
-            info!("Capturing frames...");
-            while task_running.load(Ordering::SeqCst) {
-                // Get frame from buffer
-                let rgb_frame = cam_iter.next().unwrap();
-                // Convert RGB frame to grayscale
-                let grayscale_image = ImageBuffer::from_fn(camera_width, camera_height, |x, y| {
-                    let rgb_pixel = *rgb_frame.get_pixel(x, y);
-                    let gray_value = rgb_pixel[0] as u32 * 77
-                        + rgb_pixel[1] as u32 * 150
-                        + rgb_pixel[2] as u32 * 29;
-                    Luma([((gray_value >> 8) & 0xFF) as u8])
-                });
-                // Get Image data from buffer data
-                let grayscale_image_data =
-                    ImageData::new(&grayscale_image, camera_width, camera_height);
-
-                // Detect face data in provided image data
-                let faces = detector.detect(&grayscale_image_data);
-
-                // Initialize zero values if face not found
-                let mut facial_data = WebcamFacialData::default();
-
-                // Get face with maximum human face probability (best candidate)
-                let max_face = faces.iter().max_by_key(|p| p.score() as i32);
-                match max_face {
-                    Some(max_face) => {
-                        debug!("Max score face: {:?}", max_face);
-                        // Take face rectangle coords and score
-                        facial_data.x = faces[0].bbox().x() as f32;
-                        facial_data.y = faces[0].bbox().y() as f32;
-                        facial_data.width = faces[0].bbox().width() as f32;
-                        facial_data.height = faces[0].bbox().height() as f32;
-                        facial_data.score = faces[0].score() as f32;
-
-                        // Calculate the scale factor to map the camera resolution
-                        let w_scale_factor = 100.0 / camera_width as f32;
-                        let h_scale_factor = 100.0 / camera_width as f32;
-
-                        // Calculate the coordinates and dimensions in the desired range (-50.0) to (50.0)
-                        facial_data.x = facial_data.x * w_scale_factor - 50.0;
-                        facial_data.y = facial_data.y * h_scale_factor - 50.0;
-                        facial_data.width = facial_data.width * w_scale_factor;
-                        facial_data.height = facial_data.height as f32 * h_scale_factor;
-                        facial_data.center_x = (2.0 * facial_data.x + facial_data.width) / -2.0; // minus flips values so negative is left
-                        facial_data.center_y = (2.0 * facial_data.y + facial_data.height) / 2.0;
-                    }
-                    None => {
-                        debug!("No faces found. Using default zero values.");
-                    }
+                if let Some(new_emotions) = classified {
+                    emotions = Some(match emotions {
+                        Some(mut emotions) => {
+                            emotions.smooth(&new_emotions, emotion_alpha);
+                            emotions
+                        }
+                        None => new_emotions,
+                    });
                 }
+            }
+            if let Some((yaw, pitch, roll)) = head_pose(&landmarks, principal_point, focal_length) {
+                facial_data.yaw = yaw;
+                facial_data.pitch = pitch;
+                facial_data.roll = roll;
+            }
+            if let Some(mouth_open) = landmarks.mouth_openness() {
+                facial_data.mouth_open = mouth_open;
+            }
+            if let Some(smile) = landmarks.smile() {
+                facial_data.smile = smile;
+            }
+            // All detected faces, for debugging
+            let all_faces: Vec<WebcamFacialData> = faces
+                .iter()
+                .map(|face| facial_data_from_face(face, camera_width))
+                .collect();
+            let raw_data = facial_data.clone();
+            // Drop detections jumping away from current track before smoothing
+            if gate.check(&facial_data) {
                 filtered_data.push(facial_data);
+            } else {
+                debug!("Face detection rejected as outlier.");
+                rejected_frames.fetch_add(1, Ordering::SeqCst);
+            }
 
-                // Send processed and filtered data
-                match sender_clone.send(filtered_data.get()) {
-                    Ok(()) => {
-                        debug!("Data from task sent.")
-                    }
-                    Err(SendError(data)) => {
-                        error!("Failed to send task data: {:?}", data);
-                    }
-                }
+            let filtered = filtered_data.get();
+
+            // Crop face box detected in this frame, filtered one lags behind moving face
+            let crop_face = match face_crop_type {
+                FaceCropType::Live => true,
+                FaceCropType::OnDemand => face_crop_requested.load(Ordering::SeqCst),
+                FaceCropType::NoCrop => false,
+            };
+            let mut face_crop = None;
+            if crop_face && raw_data.score > 0.0 {
+                face_crop = Some(face_crop_frame(
+                    &raw_data,
+                    camera_width,
+                    camera_height,
+                    face_crop_padding,
+                    face_crop_size,
+                    rgb_pixel,
+                ));
+                face_crop_requested.store(false, Ordering::SeqCst);
             }
-            true
-        });
-        commands.spawn(WebcamFacialTask(task));
-        // Set flag that we started thread
-        webcam_facial.status.store(true, Ordering::SeqCst);
-    }
-    // If not enabled and task is running set flag to stop
-    if !webcam_facial.control & webcam_facial.status.load(Ordering::SeqCst) {
-        webcam_facial.status.store(false, Ordering::SeqCst);
-    }
-    for (entity, mut task) in &mut plugin_task {
-        if let Some(status) = future::block_on(future::poll_once(&mut task.0)) {
-            // Task completed, so remove task component from entity
-            commands.entity(entity).remove::<WebcamFacialTask>();
-            webcam_facial.status.store(false, Ordering::SeqCst);
-            webcam_facial.control = false;
-            if status {
-                info!("Camera stopped.");
-            } else {
-                info!("Plugin setup failed. Plugin self disabled.");
+
+            // Full size snapshot if requested
+            let snapshot = match snapshot_receiver.try_recv() {
+                Ok(request) => Some(take_snapshot(
+                    request,
+                    camera_width,
+                    camera_height,
+                    &all_faces,
+                    &raw_data,
+                    rgb_pixel,
+                )),
+                Err(_) => None,
+            };
+
+            // Send processed and filtered data
+            let task_data = WebcamFacialTaskData {
+                frame_time,
+                raw: raw_data,
+                faces: all_faces,
+                filtered,
+                landmarks,
+                #[cfg(feature = "emotion")]
+                emotions: emotions.unwrap_or_default(),
+                preview,
+                face_crop,
+                snapshot,
+            };
+            match sender_clone.send(task_data) {
+                Ok(()) => {
+                    debug!("Data from task sent.")
+                }
+                // Receiver is gone with despawned camera entity, release webcam
+                Err(SendError(_)) => {
+                    info!("Task data receiver dropped, stopping capture.");
+                    break;
+                }
             }
         }
-    }
-    while let Ok(data) = webcam_facial.receiver.try_recv() {
-        debug!("Send Bevy event {:?}", data);
-        plugin_events.send(WebcamFacialDataEvent(data));
-    }
+        WebcamFacialTaskExit::Stopped
+    })
 }
 
-fn get_camera_frame_iterator(
-    camera_device: u32,
-    camera_width: u32,
-    camera_height: u32,
-    camera_framerate: u32,
-) -> Option<camera_capture::ImageIterator> {
-    // Create the camera device
-    let camera_device = match camera_capture::create(camera_device) {
-        Ok(device) => {
-            #[cfg(unix)]
-            info!("Using '/dev/video{}' camera.", camera_device);
-            #[cfg(windows)]
-            info!("Using camera ID:{}.", camera_device);
-            device
-        }
-        Err(err) => {
-            error!(
-                "Error creating camera device [{}]: {:?}",
-                camera_device, err
-            );
-            return None;
-        }
-    };
-    // Set the resolution
-    let resolution_device = match camera_device.resolution(camera_width, camera_height) {
-        Ok(resolution) => {
-            info!(
-                "Camera resolution set to {}x{}.",
-                camera_width, camera_height
-            );
-            resolution
-        }
-        Err(err) => {
-            error!("Error setting camera resolution: {:?}", err);
-            return None;
-        }
-    };
-    // Set the frame rate and start the camera capture
-    let cam_iter = match resolution_device.fps(camera_framerate as f64) {
-        Ok(fps) => {
-            info!("Camera fps set to {}.", camera_framerate);
-            fps.start()
-        }
-        Err(err) => {
-            error!("Error setting camera frame rate: {:?}", err);
-            return None;
-        }
+// Maps face rectangle coords and score to WebcamFacialData
+fn facial_data_from_face(face: &FaceDetection, camera_width: u32) -> WebcamFacialData {
+    // Take face rectangle coords and score
+    let mut facial_data = WebcamFacialData {
+        x: face.x,
+        y: face.y,
+        width: face.width,
+        height: face.height,
+        score: face.score,
+        ..Default::default()
     };
-    match cam_iter {
-        Ok(iter) => Some(iter),
-        Err(err) => {
-            error!("Error starting camera: {:?}", err);
-            None
-        }
-    }
+
+    // Calculate the scale factor to map the camera resolution
+    let w_scale_factor = 100.0 / camera_width as f32;
+    let h_scale_factor = 100.0 / camera_width as f32;
+
+    // Calculate the coordinates and dimensions in the desired range (-50.0) to (50.0)
+    facial_data.x = facial_data.x * w_scale_factor - 50.0;
+    facial_data.y = facial_data.y * h_scale_factor - 50.0;
+    facial_data.width *= w_scale_factor;
+    facial_data.height *= h_scale_factor;
+    facial_data.center_x = (2.0 * facial_data.x + facial_data.width) / -2.0; // minus flips values so negative is left
+    facial_data.center_y = (2.0 * facial_data.y + facial_data.height) / 2.0;
+    facial_data
+}
+
+fn webcam_facial_diagnostic_system(
+    webcam_facial: Res<WebcamFacialController>,
+    mut diagnostics: Diagnostics,
+) {
+    diagnostics.add_measurement(WebcamFacialPlugin::REJECTED_FRAMES, || {
+        webcam_facial.rejected_frames.load(Ordering::SeqCst) as f64
+    });
 }
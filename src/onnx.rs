@@ -0,0 +1,268 @@
+// ONNX models run on CPU with tract: UltraFace (RFB-320) face detector, PFLD landmarks
+// and FER+ emotions
+use bevy::{
+    log::{error, info},
+    math::Vec2,
+};
+use tract_onnx::prelude::*;
+
+use crate::{DetectorFrame, FaceDetection, FaceDetector, FaceLandmarker};
+#[cfg(feature = "emotion")]
+use crate::{EmotionClassifier, FaceEmotions};
+
+const INPUT_WIDTH: usize = 320;
+const INPUT_HEIGHT: usize = 240;
+const LANDMARKS_INPUT_SIZE: usize = 112;
+// Crop around face box is this much larger than its bigger side
+const LANDMARKS_CROP_SCALE: f32 = 1.1;
+#[cfg(feature = "emotion")]
+const EMOTIONS_INPUT_SIZE: usize = 64;
+// Overlap above which box with lower score is dropped
+const NMS_IOU_THRESHOLD: f32 = 0.3;
+
+pub(crate) struct UltraFaceDetector {
+    model: TypedRunnableModel<TypedModel>,
+    score_threshold: f32,
+}
+
+impl UltraFaceDetector {
+    pub(crate) fn new(model_path: &str, score_threshold: f32) -> TractResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_path(model_path)?
+            .with_input_fact(0, f32::fact([1, 3, INPUT_HEIGHT, INPUT_WIDTH]).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        info!("Using {} recognition model.", model_path);
+        Ok(Self {
+            model,
+            score_threshold,
+        })
+    }
+
+    fn run(&self, frame: &DetectorFrame) -> TractResult<Vec<FaceDetection>> {
+        // Resize to model input by skipping pixels, normalize to about -1.0 .. 1.0
+        let frame_width = frame.width as usize;
+        let frame_height = frame.height as usize;
+        let input: Tensor = tract_ndarray::Array4::from_shape_fn(
+            (1, 3, INPUT_HEIGHT, INPUT_WIDTH),
+            |(_, channel, y, x)| {
+                let frame_x = x * frame_width / INPUT_WIDTH;
+                let frame_y = y * frame_height / INPUT_HEIGHT;
+                let value = frame.rgb[(frame_y * frame_width + frame_x) * 3 + channel];
+                (value as f32 - 127.0) / 128.0
+            },
+        )
+        .into();
+        let outputs = self.model.run(tvec!(input.into()))?;
+        // Background/face probabilities [1, N, 2] and corner boxes [1, N, 4] relative to frame
+        let scores = outputs[0]
+            .to_array_view::<f32>()?
+            .into_dimensionality::<tract_ndarray::Ix3>()?;
+        let boxes = outputs[1]
+            .to_array_view::<f32>()?
+            .into_dimensionality::<tract_ndarray::Ix3>()?;
+        let mut faces = Vec::new();
+        for index in 0..scores.shape()[1] {
+            let score = scores[[0, index, 1]];
+            if score < self.score_threshold {
+                continue;
+            }
+            let left = boxes[[0, index, 0]] * frame.width as f32;
+            let top = boxes[[0, index, 1]] * frame.height as f32;
+            let right = boxes[[0, index, 2]] * frame.width as f32;
+            let bottom = boxes[[0, index, 3]] * frame.height as f32;
+            faces.push(FaceDetection {
+                x: left,
+                y: top,
+                width: right - left,
+                height: bottom - top,
+                score,
+            });
+        }
+        Ok(non_maximum_suppression(faces, NMS_IOU_THRESHOLD))
+    }
+}
+
+impl FaceDetector for UltraFaceDetector {
+    fn detect(&mut self, frame: &DetectorFrame) -> Vec<FaceDetection> {
+        match self.run(frame) {
+            Ok(faces) => faces,
+            Err(error) => {
+                error!("Face detection failed: {}", error);
+                Vec::new()
+            }
+        }
+    }
+}
+
+pub(crate) struct PfldLandmarker {
+    model: TypedRunnableModel<TypedModel>,
+}
+
+impl PfldLandmarker {
+    pub(crate) fn new(model_path: &str) -> TractResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_path(model_path)?
+            .with_input_fact(
+                0,
+                f32::fact([1, 3, LANDMARKS_INPUT_SIZE, LANDMARKS_INPUT_SIZE]).into(),
+            )?
+            .into_optimized()?
+            .into_runnable()?;
+        info!("Using {} landmarks model.", model_path);
+        Ok(Self { model })
+    }
+
+    fn run(&self, frame: &DetectorFrame, face: &FaceDetection) -> TractResult<Vec<Vec2>> {
+        // Square crop around face, area outside of frame is black
+        let crop_size = face.width.max(face.height) * LANDMARKS_CROP_SCALE;
+        let left = face.x + face.width / 2.0 - crop_size / 2.0;
+        let top = face.y + face.height / 2.0 - crop_size / 2.0;
+        let step = crop_size / LANDMARKS_INPUT_SIZE as f32;
+        let frame_width = frame.width as usize;
+        // Model is trained on BGR images with values 0.0 .. 1.0
+        let input: Tensor = tract_ndarray::Array4::from_shape_fn(
+            (1, 3, LANDMARKS_INPUT_SIZE, LANDMARKS_INPUT_SIZE),
+            |(_, channel, y, x)| {
+                let frame_x = (left + x as f32 * step).floor();
+                let frame_y = (top + y as f32 * step).floor();
+                if frame_x < 0.0
+                    || frame_y < 0.0
+                    || frame_x >= frame.width as f32
+                    || frame_y >= frame.height as f32
+                {
+                    return 0.0;
+                }
+                let index = (frame_y as usize * frame_width + frame_x as usize) * 3 + 2 - channel;
+                frame.rgb[index] as f32 / 255.0
+            },
+        )
+        .into();
+        let outputs = self.model.run(tvec!(input.into()))?;
+        // Landmarks are output with 68 x/y pairs relative to crop, other outputs are features
+        let landmarks = match outputs.iter().find(|output| output.len() == 136) {
+            Some(landmarks) => landmarks.as_slice::<f32>()?,
+            None => return Ok(Vec::new()),
+        };
+        Ok(landmarks
+            .chunks(2)
+            .map(|point| Vec2::new(left + point[0] * crop_size, top + point[1] * crop_size))
+            .collect())
+    }
+}
+
+impl FaceLandmarker for PfldLandmarker {
+    fn landmarks(&mut self, frame: &DetectorFrame, face: &FaceDetection) -> Vec<Vec2> {
+        match self.run(frame, face) {
+            Ok(points) => points,
+            Err(error) => {
+                error!("Landmark detection failed: {}", error);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "emotion")]
+pub(crate) struct FerPlusClassifier {
+    model: TypedRunnableModel<TypedModel>,
+}
+
+#[cfg(feature = "emotion")]
+impl FerPlusClassifier {
+    pub(crate) fn new(model_path: &str) -> TractResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_path(model_path)?
+            .with_input_fact(
+                0,
+                f32::fact([1, 1, EMOTIONS_INPUT_SIZE, EMOTIONS_INPUT_SIZE]).into(),
+            )?
+            .into_optimized()?
+            .into_runnable()?;
+        info!("Using {} emotions model.", model_path);
+        Ok(Self { model })
+    }
+
+    fn run(
+        &self,
+        frame: &DetectorFrame,
+        face: &FaceDetection,
+    ) -> TractResult<Option<FaceEmotions>> {
+        // Square crop of face box, area outside of frame is black
+        let crop_size = face.width.max(face.height);
+        let left = face.x + face.width / 2.0 - crop_size / 2.0;
+        let top = face.y + face.height / 2.0 - crop_size / 2.0;
+        let step = crop_size / EMOTIONS_INPUT_SIZE as f32;
+        let frame_width = frame.width as usize;
+        // Model is trained on grayscale images with values 0.0 .. 255.0
+        let input: Tensor = tract_ndarray::Array4::from_shape_fn(
+            (1, 1, EMOTIONS_INPUT_SIZE, EMOTIONS_INPUT_SIZE),
+            |(_, _, y, x)| {
+                let frame_x = (left + x as f32 * step).floor();
+                let frame_y = (top + y as f32 * step).floor();
+                if frame_x < 0.0
+                    || frame_y < 0.0
+                    || frame_x >= frame.width as f32
+                    || frame_y >= frame.height as f32
+                {
+                    return 0.0;
+                }
+                frame.grayscale[frame_y as usize * frame_width + frame_x as usize] as f32
+            },
+        )
+        .into();
+        let outputs = self.model.run(tvec!(input.into()))?;
+        // Scores of 8 emotions in Emotion order, turned to probabilities with softmax
+        let scores: [f32; 8] = match outputs[0].as_slice::<f32>()?.try_into() {
+            Ok(scores) => scores,
+            Err(_) => return Ok(None),
+        };
+        let max_score = scores.iter().copied().fold(f32::MIN, f32::max);
+        let exponents = scores.map(|score| (score - max_score).exp());
+        let sum: f32 = exponents.iter().sum();
+        Ok(Some(FaceEmotions::from_array(
+            exponents.map(|exponent| exponent / sum),
+        )))
+    }
+}
+
+#[cfg(feature = "emotion")]
+impl EmotionClassifier for FerPlusClassifier {
+    fn classify(&mut self, frame: &DetectorFrame, face: &FaceDetection) -> Option<FaceEmotions> {
+        match self.run(frame, face) {
+            Ok(emotions) => emotions,
+            Err(error) => {
+                error!("Emotion classification failed: {}", error);
+                None
+            }
+        }
+    }
+}
+
+// Keeps box with best score out of overlapping ones
+fn non_maximum_suppression(
+    mut faces: Vec<FaceDetection>,
+    iou_threshold: f32,
+) -> Vec<FaceDetection> {
+    faces.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut kept: Vec<FaceDetection> = Vec::new();
+    for face in faces {
+        if kept
+            .iter()
+            .all(|kept_face| intersection_over_union(kept_face, &face) <= iou_threshold)
+        {
+            kept.push(face);
+        }
+    }
+    kept
+}
+
+fn intersection_over_union(a: &FaceDetection, b: &FaceDetection) -> f32 {
+    let width = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let height = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    if width <= 0.0 || height <= 0.0 {
+        return 0.0;
+    }
+    let intersection = width * height;
+    intersection / (a.width * a.height + b.width * b.height - intersection)
+}
@@ -0,0 +1,202 @@
+// Head-coupled off-axis perspective: screen behaves like a window into the scene
+use bevy::{
+    app::{App, Plugin, PostUpdate, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::Added,
+        reflect::ReflectComponent,
+        schedule::IntoSystemConfigs,
+        system::{Commands, Query, Res},
+    },
+    math::{Mat4, Vec2, Vec3, Vec4},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::{
+        camera::{camera_system, CameraProjection, CameraProjectionPlugin, Projection},
+        view::{update_frusta, VisibilitySystems},
+    },
+    transform::{components::Transform, TransformSystem},
+};
+
+use crate::{FaceCalibration, FaceDepth, FaceState, WebcamFacialSet, WebcamFacialState};
+
+// Add to plugins together with WebcamFacialPlugin to use HeadCoupledProjection on cameras
+pub struct HeadCoupledCameraPlugin;
+
+impl Plugin for HeadCoupledCameraPlugin {
+    fn build(&self, app: &mut App) {
+        // Camera follows head every frame whatever config_schedule of WebcamFacialPlugin is
+        app.add_plugins(CameraProjectionPlugin::<HeadCoupledFrustum>::default())
+            .add_systems(
+                Update,
+                (
+                    head_coupled_projection_setup,
+                    head_coupled_eye_system.after(WebcamFacialSet::Process),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                update_frusta::<HeadCoupledFrustum>
+                    .in_set(VisibilitySystems::UpdateProjectionFrusta)
+                    .after(camera_system::<HeadCoupledFrustum>)
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+// Insert on Camera3d entity to turn it into head-coupled window view.
+// Camera Transform is driven by plugin, move `window` to move the view.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+pub struct HeadCoupledProjection {
+    // Pose of virtual window (screen center) in world, view goes through it along -Z
+    pub window: Transform,
+    // Width of virtual window in world units, height follows viewport aspect ratio
+    pub window_width: f32,
+    // Eye movement in world units for calibrated face offset of 1.0
+    pub head_range: Vec2,
+    // Distance of eye from window in world units
+    pub eye_distance: f32,
+    // When set, eye distance is estimated face distance in meters multiplied by this scale
+    pub distance_scale: Option<f32>,
+    pub near: f32,
+    pub far: f32,
+    // Eye position relative to window, updated from face data
+    pub eye: Vec3,
+}
+
+impl Default for HeadCoupledProjection {
+    fn default() -> Self {
+        Self {
+            window: Transform::IDENTITY,
+            window_width: 1.0,
+            head_range: Vec2::new(0.3, 0.2),
+            eye_distance: 1.0,
+            distance_scale: None,
+            near: 0.1,
+            far: 1000.0,
+            eye: Vec3::new(0.0, 0.0, 1.0),
+        }
+    }
+}
+
+// Projection of camera used by Bevy camera systems, copied from HeadCoupledProjection together
+// with viewport aspect ratio Bevy updates
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+struct HeadCoupledFrustum {
+    window_width: f32,
+    near: f32,
+    far: f32,
+    eye: Vec3,
+    aspect_ratio: f32,
+}
+
+impl Default for HeadCoupledFrustum {
+    fn default() -> Self {
+        HeadCoupledProjection::default().into()
+    }
+}
+
+impl From<HeadCoupledProjection> for HeadCoupledFrustum {
+    fn from(projection: HeadCoupledProjection) -> Self {
+        Self {
+            window_width: projection.window_width,
+            near: projection.near,
+            far: projection.far,
+            eye: projection.eye,
+            aspect_ratio: 1.0,
+        }
+    }
+}
+
+impl CameraProjection for HeadCoupledFrustum {
+    // Asymmetric frustum through window edges, with infinite reverse z like PerspectiveProjection
+    fn get_projection_matrix(&self) -> Mat4 {
+        let half_width = self.window_width / 2.0;
+        let half_height = half_width / self.aspect_ratio;
+        // Eye can't go through window
+        let scale = self.near / self.eye.z.max(self.near);
+        let left = (-half_width - self.eye.x) * scale;
+        let right = (half_width - self.eye.x) * scale;
+        let bottom = (-half_height - self.eye.y) * scale;
+        let top = (half_height - self.eye.y) * scale;
+        Mat4::from_cols(
+            Vec4::new(2.0 * self.near / (right - left), 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 * self.near / (top - bottom), 0.0, 0.0),
+            Vec4::new(
+                (right + left) / (right - left),
+                (top + bottom) / (top - bottom),
+                0.0,
+                -1.0,
+            ),
+            Vec4::new(0.0, 0.0, self.near, 0.0),
+        )
+    }
+
+    fn update(&mut self, width: f32, height: f32) {
+        self.aspect_ratio = width / height;
+    }
+
+    fn far(&self) -> f32 {
+        self.far
+    }
+}
+
+// Camera can have only one projection, so default one from Camera3dBundle is replaced
+fn head_coupled_projection_setup(
+    mut commands: Commands,
+    cameras: Query<(Entity, &HeadCoupledProjection), Added<HeadCoupledProjection>>,
+) {
+    for (entity, projection) in &cameras {
+        commands
+            .entity(entity)
+            .remove::<Projection>()
+            .insert(HeadCoupledFrustum::from(projection.clone()));
+    }
+}
+
+fn head_coupled_eye_system(
+    facial_state: Res<WebcamFacialState>,
+    face_state: Option<Res<FaceState>>,
+    calibration: Res<FaceCalibration>,
+    depth: Res<FaceDepth>,
+    mut cameras: Query<(
+        &mut HeadCoupledProjection,
+        &mut HeadCoupledFrustum,
+        &mut Transform,
+    )>,
+) {
+    // Prefer render-rate interpolated data if enabled
+    let data = match &face_state {
+        Some(face_state) => &face_state.data,
+        None => &facial_state.filtered,
+    };
+    // Face y grows downwards, eye y grows upwards
+    let offset = Vec2::new(
+        calibration.remap_x(data.center_x),
+        -calibration.remap_y(data.center_y),
+    );
+    for (mut projection, mut frustum, mut transform) in &mut cameras {
+        let eye_distance = match projection.distance_scale {
+            Some(scale) if depth.distance > 0.0 => depth.distance * scale,
+            _ => projection.eye_distance,
+        };
+        let eye = (offset * projection.head_range).extend(eye_distance);
+        if projection.eye != eye {
+            projection.eye = eye;
+        }
+        // Projection matrix is recomputed only when frustum changes
+        let (window_width, near, far) = (projection.window_width, projection.near, projection.far);
+        if (frustum.window_width, frustum.near, frustum.far, frustum.eye)
+            != (window_width, near, far, eye)
+        {
+            frustum.window_width = window_width;
+            frustum.near = near;
+            frustum.far = far;
+            frustum.eye = eye;
+        }
+        transform.translation = projection.window.translation + projection.window.rotation * eye;
+        transform.rotation = projection.window.rotation;
+    }
+}
@@ -0,0 +1,124 @@
+// Head pose from facial landmarks, POSIT solver with generic 3D face model
+use bevy::math::{Mat3, Vec2, Vec3};
+
+use crate::FaceLandmarks;
+
+// Generic face in arbitrary units, axes as in camera frame: x to the right, y downwards
+// and z away from camera. Nose tip, eye on the left, eye on the right, mouth corners.
+const FACE_MODEL: [Vec3; 5] = [
+    Vec3::new(0.0, 0.0, 0.0),
+    Vec3::new(-160.0, -170.0, 135.0),
+    Vec3::new(160.0, -170.0, 135.0),
+    Vec3::new(-150.0, 150.0, 125.0),
+    Vec3::new(150.0, 150.0, 125.0),
+];
+const POSIT_ITERATIONS: usize = 10;
+
+// Yaw, pitch and roll in degrees. Landmarks, principal point and focal length are in the same units.
+// Yaw grows when player turns head to own right, pitch when looking up, roll when tilting
+// head to own right shoulder, matching center_x growing to the right.
+pub(crate) fn head_pose(
+    landmarks: &FaceLandmarks,
+    principal_point: Vec2,
+    focal_length: f32,
+) -> Option<(f32, f32, f32)> {
+    let image = [
+        landmarks.nose()?,
+        landmarks.left_eye()?,
+        landmarks.right_eye()?,
+        landmarks.mouth_left()?,
+        landmarks.mouth_right()?,
+    ]
+    .map(|point| point - principal_point);
+    let rotation = posit(&FACE_MODEL, &image, focal_length)?;
+    // Direction face looks to and top of head in camera space
+    let forward = rotation * Vec3::NEG_Z;
+    let up = rotation * Vec3::NEG_Y;
+    let yaw = (-forward.x).atan2(-forward.z);
+    let pitch = (-forward.y).clamp(-1.0, 1.0).asin();
+    let roll = (-up.x).atan2(-up.y);
+    Some((yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees()))
+}
+
+// Rotation from model to camera space, DeMenthon & Davis POSIT
+fn posit(model: &[Vec3; 5], image: &[Vec2; 5], focal_length: f32) -> Option<Mat3> {
+    // Pseudo-inverse of model points relative to first one
+    let object: Vec<Vec3> = model[1..].iter().map(|point| *point - model[0]).collect();
+    let object_inverse = object
+        .iter()
+        .fold(Mat3::ZERO, |sum, v| {
+            sum + Mat3::from_cols(*v * v.x, *v * v.y, *v * v.z)
+        })
+        .inverse();
+    // Perspective correction of image points, zero gives scaled orthographic projection
+    let mut epsilon = [0.0; 5];
+    let mut rotation = Mat3::IDENTITY;
+    for _ in 0..POSIT_ITERATIONS {
+        let mut sum_x = Vec3::ZERO;
+        let mut sum_y = Vec3::ZERO;
+        for (index, point) in object.iter().enumerate() {
+            let image_point = image[index + 1] * (1.0 + epsilon[index + 1]) - image[0];
+            sum_x += *point * image_point.x;
+            sum_y += *point * image_point.y;
+        }
+        let i = object_inverse * sum_x;
+        let j = object_inverse * sum_y;
+        let (i_length, j_length) = (i.length(), j.length());
+        if i_length <= f32::EPSILON || j_length <= f32::EPSILON {
+            return None;
+        }
+        let scale = (i_length * j_length).sqrt();
+        let i = i / i_length;
+        let k = i.cross(j / j_length).normalize();
+        let j = k.cross(i);
+        let distance = focal_length / scale;
+        for (index, point) in object.iter().enumerate() {
+            epsilon[index + 1] = point.dot(k) / distance;
+        }
+        rotation = Mat3::from_cols(i, j, k).transpose();
+    }
+    Some(rotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOCAL_LENGTH: f32 = 800.0;
+
+    // Perspective projection of face model rotated and placed in front of camera
+    fn project(rotation: Mat3, distance: f32) -> [Vec2; 5] {
+        FACE_MODEL.map(|point| {
+            let point = rotation * point + Vec3::new(0.0, 0.0, distance);
+            Vec2::new(point.x, point.y) * FOCAL_LENGTH / point.z
+        })
+    }
+
+    #[test]
+    fn posit_recovers_rotation() {
+        let rotation = Mat3::from_rotation_y(0.3) * Mat3::from_rotation_x(-0.2);
+        let estimate = posit(&FACE_MODEL, &project(rotation, 2000.0), FOCAL_LENGTH).unwrap();
+        for (estimated, expected) in [
+            (estimate.x_axis, rotation.x_axis),
+            (estimate.y_axis, rotation.y_axis),
+            (estimate.z_axis, rotation.z_axis),
+        ] {
+            assert!((estimated - expected).length() < 0.02);
+        }
+    }
+
+    #[test]
+    fn posit_rejects_collapsed_points() {
+        assert!(posit(&FACE_MODEL, &[Vec2::ZERO; 5], FOCAL_LENGTH).is_none());
+    }
+
+    #[test]
+    fn frontal_face_has_zero_pose() {
+        let [nose, left_eye, right_eye, mouth_left, mouth_right] = project(Mat3::IDENTITY, 2000.0);
+        let landmarks = FaceLandmarks {
+            points: vec![left_eye, right_eye, nose, mouth_left, mouth_right],
+        };
+        let (yaw, pitch, roll) = head_pose(&landmarks, Vec2::ZERO, FOCAL_LENGTH).unwrap();
+        assert!(yaw.abs() < 1.0 && pitch.abs() < 1.0 && roll.abs() < 1.0);
+    }
+}
@@ -0,0 +1,119 @@
+// Live webcam preview as Bevy Image texture, Image side needs `textures` feature
+use bevy::ecs::system::Resource;
+#[cfg(feature = "textures")]
+use bevy::{
+    asset::{Assets, Handle, HandleUntyped},
+    ecs::system::{Res, ResMut},
+    reflect::TypeUuid,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::Image,
+    },
+};
+
+#[cfg(feature = "textures")]
+pub const WEBCAM_PREVIEW_IMAGE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 8316457092835734103);
+
+#[derive(Clone, Copy)]
+pub enum WebcamPreviewType {
+    Color,
+    Grayscale,
+    NoPreview,
+}
+
+// Handle of Image updated with every captured frame, use it in UI or materials
+#[cfg(feature = "textures")]
+#[derive(Resource, Clone)]
+pub struct WebcamFacialPreview {
+    pub image: Handle<Image>,
+}
+
+#[cfg(feature = "textures")]
+impl Default for WebcamFacialPreview {
+    fn default() -> Self {
+        Self {
+            image: WEBCAM_PREVIEW_IMAGE_HANDLE.typed(),
+        }
+    }
+}
+
+// RGBA pixels of downscaled frame prepared in webcam task
+#[derive(Clone)]
+pub struct WebcamPreviewFrame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl std::fmt::Debug for WebcamPreviewFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WebcamPreviewFrame({}x{})", self.width, self.height)
+    }
+}
+
+// Latest preview frame received from task, waiting for upload to Image
+#[derive(Resource, Default)]
+pub(crate) struct WebcamPreviewFrameBuffer(pub Option<WebcamPreviewFrame>);
+
+// Downscales frame by skipping pixels, `pixel` returns RGB of full size frame
+pub(crate) fn preview_frame(
+    width: u32,
+    height: u32,
+    preview_type: WebcamPreviewType,
+    downscale: u32,
+    pixel: impl Fn(u32, u32) -> [u8; 3],
+) -> Option<WebcamPreviewFrame> {
+    let grayscale = match preview_type {
+        WebcamPreviewType::Color => false,
+        WebcamPreviewType::Grayscale => true,
+        WebcamPreviewType::NoPreview => return None,
+    };
+    let downscale = downscale.max(1);
+    let preview_width = width / downscale;
+    let preview_height = height / downscale;
+    let mut data = Vec::with_capacity((preview_width * preview_height * 4) as usize);
+    for y in 0..preview_height {
+        for x in 0..preview_width {
+            let [r, g, b] = pixel(x * downscale, y * downscale);
+            if grayscale {
+                let gray_value = r as u32 * 77 + g as u32 * 150 + b as u32 * 29;
+                let gray = ((gray_value >> 8) & 0xFF) as u8;
+                data.extend_from_slice(&[gray, gray, gray, 255]);
+            } else {
+                data.extend_from_slice(&[r, g, b, 255]);
+            }
+        }
+    }
+    Some(WebcamPreviewFrame {
+        width: preview_width,
+        height: preview_height,
+        data,
+    })
+}
+
+// Image asset from RGBA frame
+#[cfg(feature = "textures")]
+pub(crate) fn frame_image(frame: WebcamPreviewFrame) -> Image {
+    Image::new(
+        Extent3d {
+            width: frame.width,
+            height: frame.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        frame.data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+#[cfg(feature = "textures")]
+pub(crate) fn webcam_preview_system(
+    preview: Res<WebcamFacialPreview>,
+    mut frame_buffer: ResMut<WebcamPreviewFrameBuffer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if let Some(frame) = frame_buffer.0.take() {
+        images.set_untracked(&preview.image, frame_image(frame));
+    }
+}
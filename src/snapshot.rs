@@ -0,0 +1,186 @@
+// Full resolution snapshot of webcam frame, optionally annotated with detections
+#[cfg(feature = "textures")]
+use bevy::{
+    asset::{Assets, Handle},
+    render::texture::Image,
+};
+use bevy::{
+    ecs::{
+        event::{Event, EventReader, EventWriter},
+        system::{Res, ResMut, Resource},
+    },
+    log::{error, info},
+    math::Vec2,
+    tasks::{IoTaskPool, Task},
+};
+use futures_lite::future;
+use image::{ColorType, ImageBuffer, Rgba, RgbaImage};
+use std::path::PathBuf;
+
+#[cfg(feature = "textures")]
+use crate::preview::frame_image;
+use crate::{depth::face_to_frame, WebcamFacialController, WebcamFacialData, WebcamPreviewFrame};
+
+// Send to capture next frame
+#[derive(Event, Clone, Debug, Default)]
+pub struct CaptureSnapshot {
+    // Draw detected faces (yellow) and selected face (red) over frame
+    pub annotate: bool,
+    // Also write frame to this PNG file
+    pub path: Option<PathBuf>,
+}
+
+// Sent with captured frame. Image is None if app has no Assets<Image>
+#[derive(Event, Clone, Debug)]
+pub struct SnapshotCaptured {
+    #[cfg(feature = "textures")]
+    pub image: Option<Handle<Image>>,
+    // Written PNG file, None if not requested or writing failed
+    pub path: Option<PathBuf>,
+}
+
+// Snapshot taken in webcam task
+#[derive(Clone, Debug)]
+pub struct WebcamSnapshot {
+    pub frame: WebcamPreviewFrame,
+    // Requested PNG path, written by snapshot system
+    pub path: Option<PathBuf>,
+}
+
+// Latest snapshot received from task
+#[derive(Resource, Default)]
+pub(crate) struct SnapshotFrameBuffer(pub Option<WebcamSnapshot>);
+
+// Snapshots waiting for their PNG to be written
+#[derive(Resource, Default)]
+pub(crate) struct SnapshotSaveTasks(Vec<Task<WebcamSnapshot>>);
+
+// Takes full size frame and annotates it, PNG is written later outside of webcam task
+pub(crate) fn take_snapshot(
+    request: CaptureSnapshot,
+    width: u32,
+    height: u32,
+    faces: &[WebcamFacialData],
+    selected: &WebcamFacialData,
+    pixel: impl Fn(u32, u32) -> [u8; 3],
+) -> WebcamSnapshot {
+    let mut image: RgbaImage = ImageBuffer::from_fn(width, height, |x, y| {
+        let [r, g, b] = pixel(x, y);
+        Rgba([r, g, b, 255])
+    });
+    if request.annotate {
+        for face in faces {
+            draw_face_box(&mut image, face, Rgba([255, 255, 0, 255]));
+        }
+        if selected.score > 0.0 {
+            draw_face_box(&mut image, selected, Rgba([255, 0, 0, 255]));
+        }
+    }
+    WebcamSnapshot {
+        frame: WebcamPreviewFrame {
+            width,
+            height,
+            data: image.into_raw(),
+        },
+        path: request.path,
+    }
+}
+
+// Writes PNG, returned snapshot has path only if file was written
+async fn save_snapshot(snapshot: WebcamSnapshot, path: PathBuf) -> WebcamSnapshot {
+    let frame = &snapshot.frame;
+    let saved = match image::save_buffer(
+        &path,
+        &frame.data,
+        frame.width,
+        frame.height,
+        ColorType::Rgba8,
+    ) {
+        Ok(()) => {
+            info!("Snapshot saved to {:?}.", path);
+            Some(path)
+        }
+        Err(error) => {
+            error!("Failed to save snapshot to {:?}: {}", path, error);
+            None
+        }
+    };
+    WebcamSnapshot {
+        path: saved,
+        ..snapshot
+    }
+}
+
+// 2 pixel wide rectangle outline of face box
+fn draw_face_box(image: &mut RgbaImage, face: &WebcamFacialData, color: Rgba<u8>) {
+    let pixels_per_unit = image.width() as f32 / 100.0;
+    let center = face_to_frame(face.center_x, face.center_y, pixels_per_unit);
+    let size = Vec2::new(face.width, face.height) * pixels_per_unit;
+    let left = (center.x - size.x / 2.0) as i64;
+    let top = (center.y - size.y / 2.0) as i64;
+    let right = left + size.x as i64;
+    let bottom = top + size.y as i64;
+    let mut put = |x: i64, y: i64| {
+        if x >= 0 && y >= 0 && x < image.width() as i64 && y < image.height() as i64 {
+            image.put_pixel(x as u32, y as u32, color);
+        }
+    };
+    for offset in 0..2 {
+        for x in left..=right {
+            put(x, top + offset);
+            put(x, bottom - offset);
+        }
+        for y in top..=bottom {
+            put(left + offset, y);
+            put(right - offset, y);
+        }
+    }
+}
+
+pub(crate) fn snapshot_system(
+    webcam_facial: Res<WebcamFacialController>,
+    mut requests: EventReader<CaptureSnapshot>,
+    mut captured: EventWriter<SnapshotCaptured>,
+    mut snapshot_buffer: ResMut<SnapshotFrameBuffer>,
+    mut save_tasks: ResMut<SnapshotSaveTasks>,
+    #[cfg(feature = "textures")] mut images: Option<ResMut<Assets<Image>>>,
+) {
+    // Pass requests to webcam task
+    for request in requests.iter() {
+        if webcam_facial
+            .snapshot_sender
+            .try_send(request.clone())
+            .is_err()
+        {
+            error!("Snapshot request dropped, previous one is not taken yet.");
+        }
+    }
+    let mut ready = Vec::new();
+    if let Some(mut snapshot) = snapshot_buffer.0.take() {
+        match snapshot.path.take() {
+            // Event is sent when PNG is written
+            Some(path) => save_tasks
+                .0
+                .push(IoTaskPool::get().spawn(save_snapshot(snapshot, path))),
+            None => ready.push(snapshot),
+        }
+    }
+    save_tasks
+        .0
+        .retain_mut(|task| match future::block_on(future::poll_once(task)) {
+            Some(snapshot) => {
+                ready.push(snapshot);
+                false
+            }
+            None => true,
+        });
+    for snapshot in ready {
+        captured.send(SnapshotCaptured {
+            #[cfg(feature = "textures")]
+            image: images
+                .as_mut()
+                .map(|images| images.add(frame_image(snapshot.frame))),
+            path: snapshot.path,
+        });
+    }
+}
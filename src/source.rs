@@ -0,0 +1,105 @@
+// Frame sources of webcam task, webcam via camera_capture by default
+use bevy::log::{error, info};
+use image::RgbImage;
+
+// Implement to feed recorded or synthetic frames instead of webcam. Frames have to be
+// of camera width x height. None ends capture and turns camera control off, so source isn't
+// started again. Source paces frame rate itself.
+pub trait FrameSource {
+    fn next_frame(&mut self) -> Option<RgbImage>;
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum FrameSourceType {
+    // Webcam with device number, resolution and frame rate of camera config
+    #[default]
+    Webcam,
+    // Source created by function with camera device number in webcam task every time it starts,
+    // so one function can serve several cameras
+    Custom(fn(u32) -> Result<Box<dyn FrameSource>, String>),
+}
+
+impl FrameSourceType {
+    pub(crate) fn create(
+        &self,
+        device: u32,
+        width: u32,
+        height: u32,
+        framerate: u32,
+    ) -> Result<Box<dyn FrameSource>, String> {
+        match self {
+            FrameSourceType::Webcam => {
+                let frames = get_camera_frame_iterator(device, width, height, framerate)
+                    .ok_or_else(|| format!("Camera {} is not available", device))?;
+                Ok(Box::new(WebcamFrameSource(frames)))
+            }
+            FrameSourceType::Custom(create) => create(device),
+        }
+    }
+}
+
+struct WebcamFrameSource(camera_capture::ImageIterator);
+
+impl FrameSource for WebcamFrameSource {
+    fn next_frame(&mut self) -> Option<RgbImage> {
+        let frame = self.0.next()?;
+        RgbImage::from_raw(frame.width(), frame.height(), frame.into_raw())
+    }
+}
+
+fn get_camera_frame_iterator(
+    camera_device: u32,
+    camera_width: u32,
+    camera_height: u32,
+    camera_framerate: u32,
+) -> Option<camera_capture::ImageIterator> {
+    // Create the camera device
+    let camera_device = match camera_capture::create(camera_device) {
+        Ok(device) => {
+            #[cfg(unix)]
+            info!("Using '/dev/video{}' camera.", camera_device);
+            #[cfg(windows)]
+            info!("Using camera ID:{}.", camera_device);
+            device
+        }
+        Err(err) => {
+            error!(
+                "Error creating camera device [{}]: {:?}",
+                camera_device, err
+            );
+            return None;
+        }
+    };
+    // Set the resolution
+    let resolution_device = match camera_device.resolution(camera_width, camera_height) {
+        Ok(resolution) => {
+            info!(
+                "Camera resolution set to {}x{}.",
+                camera_width, camera_height
+            );
+            resolution
+        }
+        Err(err) => {
+            error!("Error setting camera resolution: {:?}", err);
+            return None;
+        }
+    };
+    // Set the frame rate and start the camera capture
+    let cam_iter = match resolution_device.fps(camera_framerate as f64) {
+        Ok(fps) => {
+            info!("Camera fps set to {}.", camera_framerate);
+            fps.start()
+        }
+        Err(err) => {
+            error!("Error setting camera frame rate: {:?}", err);
+            return None;
+        }
+    };
+    match cam_iter {
+        Ok(iter) => Some(iter),
+        Err(err) => {
+            error!("Error starting camera: {:?}", err);
+            None
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// Latest face data kept as resource, for systems reading it with Res<>
+use bevy::{
+    ecs::{component::Component, system::Resource},
+    time::Time,
+};
+use std::time::Duration;
+
+#[cfg(feature = "emotion")]
+use crate::FaceEmotions;
+use crate::{FaceLandmarks, WebcamFacialData, WebcamFacialTaskData};
+
+// Updated only when new data arrives from webcam task, so it works with `resource_changed`.
+// Resource follows plugin's primary camera, component is on each WebcamFacialCamera entity.
+#[derive(Resource, Component, Default, Clone, Debug)]
+pub struct WebcamFacialState {
+    // Data of last frame before outlier gate and smoothing
+    pub raw: WebcamFacialData,
+    // Data after outlier gate and smoothing, same as sent in WebcamFacialDataEvent
+    pub filtered: WebcamFacialData,
+    // All faces detected in last frame, `raw` is the one with best score
+    pub faces: Vec<WebcamFacialData>,
+    // Landmarks of selected face, empty without landmark model or face
+    pub landmarks: FaceLandmarks,
+    // Emotion probabilities of selected face smoothed over frames, kept while face is lost
+    #[cfg(feature = "emotion")]
+    pub emotions: FaceEmotions,
+    // Frame capture time since app startup
+    pub timestamp: Duration,
+    // Face was found in last frame
+    pub present: bool,
+}
+
+impl WebcamFacialState {
+    // Time passed since last frame was captured
+    pub fn age(&self, time: &Time) -> Duration {
+        time.raw_elapsed().saturating_sub(self.timestamp)
+    }
+
+    pub(crate) fn update(&mut self, data: &WebcamFacialTaskData, timestamp: Duration) {
+        self.present = data.raw.score > 0.0;
+        self.raw = data.raw.clone();
+        self.faces = data.faces.clone();
+        self.landmarks = data.landmarks.clone();
+        #[cfg(feature = "emotion")]
+        {
+            self.emotions = data.emotions;
+        }
+        self.filtered = data.filtered.clone();
+        self.timestamp = timestamp;
+    }
+}
@@ -0,0 +1,354 @@
+// Metric head position triangulated from two cameras with known baseline
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        system::{Commands, Query},
+    },
+    math::Vec3,
+};
+
+use crate::{depth::frame_center_y, WebcamFacialCamera, WebcamFacialData, WebcamFacialState};
+
+// Spawn as entity to triangulate face seen by two cameras. Cameras are WebcamFacialCamera
+// entities (primary camera is `WebcamFacialController.entity`), mounted side by side looking
+// the same direction. Result is kept in StereoHeadPosition component of the same entity.
+#[derive(Component, Clone, Debug)]
+pub struct WebcamFacialStereo {
+    // Camera on the left side as seen by player, at -baseline / 2 of head position x
+    pub left: Entity,
+    // Camera on the right side as seen by player, at baseline / 2 of head position x
+    pub right: Entity,
+    // Distance between cameras in meters
+    pub baseline: f32,
+    // Horizontal field of view of both cameras in degrees
+    pub camera_fov: f32,
+    // Maximum difference of face center y in both views, in -50.0 .. 50.0 units
+    pub max_vertical_offset: f32,
+    // Maximum difference of frame capture times in seconds, stopped camera is not paired
+    pub max_time_offset: f32,
+}
+
+impl WebcamFacialStereo {
+    pub fn new(left: Entity, right: Entity, baseline: f32) -> Self {
+        Self {
+            left,
+            right,
+            baseline,
+            camera_fov: 60.0,
+            max_vertical_offset: 5.0,
+            max_time_offset: 0.1,
+        }
+    }
+}
+
+// Head position in meters, centered between cameras, same axes as FaceDepth
+#[derive(Component, Default, Clone, Debug)]
+pub struct StereoHeadPosition {
+    pub head_position: Vec3,
+    pub distance: f32,
+    // Difference of face center x in both views, in -50.0 .. 50.0 units
+    pub disparity: f32,
+    // Same face was matched in both views
+    pub found: bool,
+}
+
+impl StereoHeadPosition {
+    // Pinhole cameras with parallel axes, face data is in -50.0 .. 50.0 units of frame width.
+    // Aspect ratio is frame height to width of both cameras (0.75 for 640x480).
+    pub fn triangulate(
+        left: &WebcamFacialData,
+        right: &WebcamFacialData,
+        baseline: f32,
+        camera_fov: f32,
+        aspect_ratio: f32,
+    ) -> Option<Self> {
+        let focal_length = 50.0 / (camera_fov.to_radians() / 2.0).tan();
+        let disparity = left.center_x - right.center_x;
+        if disparity <= 0.0 {
+            return None;
+        }
+        let distance = focal_length * baseline / disparity;
+        let center_y = (left.center_y + right.center_y) / 2.0 - frame_center_y(aspect_ratio);
+        Some(Self {
+            head_position: Vec3::new(
+                (left.center_x + right.center_x) / 2.0 / focal_length * distance,
+                -center_y / focal_length * distance,
+                distance,
+            ),
+            distance,
+            disparity,
+            found: true,
+        })
+    }
+
+    // Indexes of the same face in both views. Faces have to lie on the same row
+    // in front of cameras, pair with the closest height and size is taken.
+    pub fn match_faces(
+        left: &[WebcamFacialData],
+        right: &[WebcamFacialData],
+        max_vertical_offset: f32,
+    ) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize, f32)> = None;
+        for (left_index, left_face) in left.iter().enumerate() {
+            for (right_index, right_face) in right.iter().enumerate() {
+                let vertical_offset = (left_face.center_y - right_face.center_y).abs();
+                if vertical_offset > max_vertical_offset
+                    || left_face.center_x <= right_face.center_x
+                {
+                    continue;
+                }
+                let cost = vertical_offset + (left_face.width - right_face.width).abs();
+                if !matches!(best, Some((_, _, best_cost)) if best_cost <= cost) {
+                    best = Some((left_index, right_index, cost));
+                }
+            }
+        }
+        best.map(|(left_index, right_index, _)| (left_index, right_index))
+    }
+}
+
+pub(crate) fn face_stereo_system(
+    mut commands: Commands,
+    camera_states: Query<(&WebcamFacialState, &WebcamFacialCamera)>,
+    mut pairs: Query<(Entity, &WebcamFacialStereo, Option<&mut StereoHeadPosition>)>,
+) {
+    // Latest camera state with its frame aspect ratio
+    let state_of = |entity: Entity| {
+        camera_states
+            .get(entity)
+            .ok()
+            .map(|(state, camera)| (state, camera.height as f32 / camera.width as f32))
+    };
+    for (entity, stereo, head_position) in pairs.iter_mut() {
+        let ((left, aspect_ratio), (right, _)) =
+            match (state_of(stereo.left), state_of(stereo.right)) {
+                (Some(left), Some(right)) => (left, right),
+                _ => continue,
+            };
+        // Frames have to be captured at about the same time
+        let time_offset = left.timestamp.max(right.timestamp) - left.timestamp.min(right.timestamp);
+        let in_sync = time_offset.as_secs_f32() <= stereo.max_time_offset;
+        let result =
+            StereoHeadPosition::match_faces(&left.faces, &right.faces, stereo.max_vertical_offset)
+                .filter(|_| in_sync)
+                .and_then(|(left_index, right_index)| {
+                    StereoHeadPosition::triangulate(
+                        &left.faces[left_index],
+                        &right.faces[right_index],
+                        stereo.baseline,
+                        stereo.camera_fov,
+                        aspect_ratio,
+                    )
+                });
+        match head_position {
+            Some(mut head_position) => match result {
+                Some(result) => *head_position = result,
+                // Keep last position, only mark face as lost
+                None => {
+                    if head_position.found {
+                        head_position.found = false;
+                    }
+                }
+            },
+            None => {
+                commands.entity(entity).insert(result.unwrap_or_default());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        depth::face_to_frame, DetectorFrame, FaceDetection, FaceDetector, FaceDetectorType,
+        FrameSource, FrameSourceType, WebcamFacialPlugin, WebcamFacialTask,
+    };
+    use bevy::{app::App, MinimalPlugins};
+    use image::{Rgb, RgbImage};
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const FOV: f32 = 60.0;
+    const ASPECT_RATIO: f32 = 0.75;
+    const BASELINE: f32 = 0.12;
+
+    // Face of 0.15 m wide head seen by camera at camera_x, head y grows upwards
+    fn view(head: Vec3, camera_x: f32) -> WebcamFacialData {
+        let focal_length = 50.0 / (FOV.to_radians() / 2.0).tan();
+        let width = focal_length * 0.15 / head.z;
+        WebcamFacialData {
+            center_x: focal_length * (head.x - camera_x) / head.z,
+            center_y: frame_center_y(ASPECT_RATIO) - focal_length * head.y / head.z,
+            width,
+            height: width,
+            score: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn views(head: Vec3) -> (WebcamFacialData, WebcamFacialData) {
+        (view(head, -BASELINE / 2.0), view(head, BASELINE / 2.0))
+    }
+
+    #[test]
+    fn triangulates_synthetic_head() {
+        let head = Vec3::new(0.05, 0.1, 0.6);
+        let (left, right) = views(head);
+        let result =
+            StereoHeadPosition::triangulate(&left, &right, BASELINE, FOV, ASPECT_RATIO).unwrap();
+        assert!((result.head_position - head).length() < 1e-4);
+        assert!((result.distance - 0.6).abs() < 1e-4);
+        assert!(result.found);
+    }
+
+    #[test]
+    fn rejects_face_without_disparity() {
+        let (left, _) = views(Vec3::new(0.0, 0.0, 0.6));
+        assert!(
+            StereoHeadPosition::triangulate(&left, &left, BASELINE, FOV, ASPECT_RATIO).is_none()
+        );
+    }
+
+    #[test]
+    fn matches_same_face_among_others() {
+        let (near_left, near_right) = views(Vec3::new(-0.1, 0.0, 0.5));
+        let (far_left, far_right) = views(Vec3::new(0.2, 0.15, 0.9));
+        let pair = StereoHeadPosition::match_faces(
+            &[near_left.clone(), far_left.clone()],
+            &[far_right.clone(), near_right.clone()],
+            5.0,
+        );
+        assert!(matches!(pair, Some((0, 1)) | Some((1, 0))));
+    }
+
+    #[test]
+    fn rejects_faces_on_different_rows() {
+        let (left, _) = views(Vec3::new(0.0, 0.0, 0.6));
+        let (_, right) = views(Vec3::new(0.0, 0.2, 0.6));
+        assert!(StereoHeadPosition::match_faces(&[left], &[right], 5.0).is_none());
+    }
+
+    const WIDTH: u32 = 320;
+    const HEIGHT: u32 = 240;
+    const HEAD: Vec3 = Vec3::new(0.05, 0.1, 0.6);
+    const FRAMES: u32 = 20;
+
+    // Head drawn as white square on black frame, device 0 is left and 1 is right camera
+    struct SyntheticFrames {
+        frame: RgbImage,
+        remaining: u32,
+    }
+
+    impl FrameSource for SyntheticFrames {
+        fn next_frame(&mut self) -> Option<RgbImage> {
+            if self.remaining == 0 {
+                return None;
+            }
+            self.remaining -= 1;
+            thread::sleep(Duration::from_millis(20));
+            Some(self.frame.clone())
+        }
+    }
+
+    fn synthetic_frames(device: u32) -> Result<Box<dyn FrameSource>, String> {
+        let camera_x = if device == 0 { -BASELINE } else { BASELINE } / 2.0;
+        let face = view(HEAD, camera_x);
+        let pixels_per_unit = WIDTH as f32 / 100.0;
+        let size = face.width * pixels_per_unit;
+        let corner = face_to_frame(face.center_x, face.center_y, pixels_per_unit) - size / 2.0;
+        let (left, top) = (corner.x, corner.y);
+        let frame = RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+            let inside = (left..left + size).contains(&(x as f32 + 0.5))
+                && (top..top + size).contains(&(y as f32 + 0.5));
+            Rgb(if inside { [255; 3] } else { [0; 3] })
+        });
+        Ok(Box::new(SyntheticFrames {
+            frame,
+            remaining: FRAMES,
+        }))
+    }
+
+    // Bounding box of bright pixels
+    struct SquareDetector;
+
+    impl FaceDetector for SquareDetector {
+        fn detect(&mut self, frame: &DetectorFrame) -> Vec<FaceDetection> {
+            let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+            for (index, _) in frame.grayscale.iter().enumerate().filter(|(_, &v)| v > 128) {
+                let (x, y) = (index as u32 % frame.width, index as u32 / frame.width);
+                (min_x, min_y) = (min_x.min(x), min_y.min(y));
+                (max_x, max_y) = (max_x.max(x), max_y.max(y));
+            }
+            if min_x > max_x {
+                return Vec::new();
+            }
+            vec![FaceDetection {
+                x: min_x as f32,
+                y: min_y as f32,
+                width: (max_x - min_x + 1) as f32,
+                height: (max_y - min_y + 1) as f32,
+                score: 1.0,
+            }]
+        }
+    }
+
+    fn square_detector() -> Result<Box<dyn FaceDetector>, String> {
+        Ok(Box::new(SquareDetector))
+    }
+
+    #[test]
+    fn triangulates_head_from_two_custom_sources() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            WebcamFacialPlugin {
+                config_webcam_autostart: false,
+                ..Default::default()
+            },
+        ));
+        let camera = |device| WebcamFacialCamera {
+            width: WIDTH,
+            height: HEIGHT,
+            frame_source: FrameSourceType::Custom(synthetic_frames),
+            detector_type: FaceDetectorType::Custom(square_detector),
+            ..WebcamFacialCamera::new(device)
+        };
+        let left = app.world.spawn(camera(0)).id();
+        let right = app.world.spawn(camera(1)).id();
+        let stereo = app
+            .world
+            .spawn(WebcamFacialStereo::new(left, right, BASELINE))
+            .id();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut result = None;
+        // Run until both sources ended and cameras turned themselves off
+        loop {
+            app.update();
+            if let Some(head_position) = app.world.get::<StereoHeadPosition>(stereo) {
+                if head_position.found {
+                    result = Some(head_position.clone());
+                }
+            }
+            let stopped = [left, right].iter().all(|&entity| {
+                let camera = app.world.entity(entity);
+                !camera.get::<WebcamFacialCamera>().unwrap().control
+                    && !camera.contains::<WebcamFacialTask>()
+            });
+            if stopped {
+                break;
+            }
+            assert!(Instant::now() < deadline, "cameras did not stop");
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let result = result.expect("head was not triangulated");
+        // One pixel of 320 is about 2% of distance at this disparity
+        assert!((result.head_position - HEAD).length() < 0.03);
+        assert!((result.distance - HEAD.z).abs() < 0.03);
+    }
+}
@@ -0,0 +1,246 @@
+// Webcams as entities, each with own task, filters and WebcamFacialState
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventWriter,
+        system::{Commands, Query, Res, ResMut},
+    },
+    log::{debug, info},
+    time::Time,
+};
+use crossbeam_channel::{bounded, never, Receiver, Sender};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc,
+};
+
+#[cfg(feature = "emotion")]
+use crate::EmotionClassifierType;
+use crate::{
+    spawn_webcam_facial_task, FaceCropFrameBuffer, FaceCropType, FaceDetectorType,
+    FaceLandmarksType, FrameSourceType, OutlierGateType, SmoothingFilterType, SnapshotFrameBuffer,
+    WebcamFacialController, WebcamFacialDataEvent, WebcamFacialPlugin, WebcamFacialState,
+    WebcamFacialTask, WebcamFacialTaskConfig, WebcamFacialTaskData, WebcamFacialTaskExit,
+    WebcamFacialTaskShared, WebcamPreviewFrameBuffer, WebcamPreviewType,
+};
+
+// Spawn as entity to capture from another webcam. Latest data is kept in WebcamFacialState
+// component of the same entity and sent in WebcamFacialDataEvent with this entity.
+// Primary camera configured in plugin is `WebcamFacialController.entity`, its capture follows
+// `WebcamFacialController.control`. Calibration, input, depth, preview and other features
+// follow primary camera only.
+#[derive(Component)]
+pub struct WebcamFacialCamera {
+    pub device: u32,
+    pub width: u32,
+    pub height: u32,
+    pub framerate: u32,
+    // Webcam by default, or recorded/synthetic frames
+    pub frame_source: FrameSourceType,
+    pub detector_type: FaceDetectorType,
+    pub landmarks_type: FaceLandmarksType,
+    // Horizontal field of view in degrees, used for head pose
+    pub camera_fov: f32,
+    // Enable/disable capture at runtime
+    pub control: bool,
+    pub filter_type: SmoothingFilterType,
+    pub filter_size_type: SmoothingFilterType,
+    pub filter_score_type: SmoothingFilterType,
+    pub filter_length: u32,
+    pub gate_type: OutlierGateType,
+    pub status: Arc<AtomicBool>,
+    // Total count of frames rejected by outlier gate
+    pub rejected_frames: Arc<AtomicU32>,
+}
+
+impl WebcamFacialCamera {
+    // Camera with same defaults as WebcamFacialPlugin
+    pub fn new(device: u32) -> Self {
+        WebcamFacialPlugin {
+            config_webcam_device: device,
+            ..Default::default()
+        }
+        .camera()
+    }
+}
+
+impl Default for WebcamFacialCamera {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+// Channel of camera task, inserted by runner so camera can be spawned with struct update syntax
+#[derive(Component)]
+pub(crate) struct WebcamFacialCameraChannel {
+    sender: Sender<WebcamFacialTaskData>,
+    receiver: Receiver<WebcamFacialTaskData>,
+}
+
+impl WebcamFacialCameraChannel {
+    pub(crate) fn new(
+        sender: Sender<WebcamFacialTaskData>,
+        receiver: Receiver<WebcamFacialTaskData>,
+    ) -> Self {
+        Self { sender, receiver }
+    }
+}
+
+impl Default for WebcamFacialCameraChannel {
+    fn default() -> Self {
+        let (sender, receiver) = bounded(1);
+        Self::new(sender, receiver)
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn webcam_facial_camera_runner(
+    mut commands: Commands,
+    mut webcam_facial: ResMut<WebcamFacialController>,
+    mut cameras: Query<(
+        Entity,
+        &mut WebcamFacialCamera,
+        Option<&WebcamFacialCameraChannel>,
+        Option<&mut WebcamFacialState>,
+        Option<&mut WebcamFacialTask>,
+    )>,
+    mut plugin_events: EventWriter<WebcamFacialDataEvent>,
+    mut facial_state: ResMut<WebcamFacialState>,
+    mut preview_frame_buffer: ResMut<WebcamPreviewFrameBuffer>,
+    mut face_crop_frame_buffer: ResMut<FaceCropFrameBuffer>,
+    mut snapshot_buffer: ResMut<SnapshotFrameBuffer>,
+    time: Res<Time>,
+) {
+    for (entity, mut camera, channel, mut state, task) in cameras.iter_mut() {
+        // Channel is added on first run, camera starts in next frame
+        let Some(channel) = channel else {
+            commands
+                .entity(entity)
+                .insert(WebcamFacialCameraChannel::default());
+            continue;
+        };
+        let primary = entity == webcam_facial.entity;
+        if primary && camera.control != webcam_facial.control {
+            camera.control = webcam_facial.control;
+        }
+        // Window focus policy pauses all cameras
+        let active = camera.control & !webcam_facial.paused;
+        let running = camera.status.load(Ordering::SeqCst);
+        // If enabled and not running - start task, previous task has to finish first
+        if active & !running & task.is_none() {
+            let task = spawn_webcam_facial_task(
+                task_config(&camera, primary.then_some(&*webcam_facial)),
+                WebcamFacialTaskShared {
+                    sender: channel.sender.clone(),
+                    status: camera.status.clone(),
+                    rejected_frames: camera.rejected_frames.clone(),
+                    face_crop_requested: if primary {
+                        webcam_facial.face_crop_requested.clone()
+                    } else {
+                        Arc::new(AtomicBool::new(false))
+                    },
+                    snapshot_receiver: if primary {
+                        webcam_facial.snapshot_receiver.clone()
+                    } else {
+                        never()
+                    },
+                },
+            );
+            commands.entity(entity).insert(WebcamFacialTask::new(task));
+            camera.status.store(true, Ordering::SeqCst);
+        }
+        // If not enabled (or paused) and task is running set flag to stop
+        if !active & running {
+            camera.status.store(false, Ordering::SeqCst);
+        }
+        if let Some(mut task) = task {
+            if let Some(exit) = task.finished() {
+                // Task completed, so remove task component from camera entity
+                commands.entity(entity).remove::<WebcamFacialTask>();
+                camera.status.store(false, Ordering::SeqCst);
+                match exit {
+                    WebcamFacialTaskExit::Stopped => info!("Camera {} stopped.", camera.device),
+                    // Restarting would fail again or replay source, so camera is disabled
+                    WebcamFacialTaskExit::SourceEnded | WebcamFacialTaskExit::Failed => {
+                        camera.control = false;
+                        if primary {
+                            webcam_facial.control = false;
+                        }
+                        let reason = match exit {
+                            WebcamFacialTaskExit::SourceEnded => "frame source ended",
+                            _ => "failed",
+                        };
+                        info!("Camera {} {}. Camera self disabled.", camera.device, reason);
+                    }
+                }
+            }
+        }
+        // State component is added with first data if camera was spawned without it
+        let mut new_state = None;
+        while let Ok(data) = channel.receiver.try_recv() {
+            debug!("Send Bevy event {:?} from {:?}", data.filtered, entity);
+            let timestamp = data.frame_time.saturating_duration_since(time.startup());
+            match state.as_mut() {
+                Some(state) => state.update(&data, timestamp),
+                None => new_state
+                    .get_or_insert_with(WebcamFacialState::default)
+                    .update(&data, timestamp),
+            }
+            // Primary camera also keeps state in resource and feeds textures
+            if primary {
+                facial_state.update(&data, timestamp);
+                if data.preview.is_some() {
+                    preview_frame_buffer.0 = data.preview;
+                }
+                if data.face_crop.is_some() {
+                    face_crop_frame_buffer.0 = data.face_crop;
+                }
+                if data.snapshot.is_some() {
+                    snapshot_buffer.0 = data.snapshot;
+                }
+            }
+            plugin_events.send(WebcamFacialDataEvent(data.filtered, timestamp, entity));
+        }
+        if let Some(new_state) = new_state {
+            commands.entity(entity).insert(new_state);
+        }
+    }
+}
+
+// Task settings from camera, primary camera adds emotions, preview and face crop of plugin
+fn task_config(
+    camera: &WebcamFacialCamera,
+    primary: Option<&WebcamFacialController>,
+) -> WebcamFacialTaskConfig {
+    WebcamFacialTaskConfig {
+        device: camera.device,
+        width: camera.width,
+        height: camera.height,
+        framerate: camera.framerate,
+        frame_source: camera.frame_source.clone(),
+        detector_type: camera.detector_type.clone(),
+        landmarks_type: camera.landmarks_type.clone(),
+        #[cfg(feature = "emotion")]
+        emotion_type: primary.map_or(EmotionClassifierType::NoEmotions, |primary| {
+            primary.config_emotion_type.clone()
+        }),
+        #[cfg(feature = "emotion")]
+        emotion_alpha: primary.map_or(1.0, |primary| primary.config_emotion_alpha),
+        camera_fov: camera.camera_fov,
+        filter_type: camera.filter_type,
+        filter_size_type: camera.filter_size_type,
+        filter_score_type: camera.filter_score_type,
+        filter_length: camera.filter_length,
+        gate_type: camera.gate_type,
+        preview_type: primary.map_or(WebcamPreviewType::NoPreview, |primary| {
+            primary.config_preview_type
+        }),
+        preview_downscale: primary.map_or(1, |primary| primary.config_preview_downscale),
+        face_crop_type: primary.map_or(FaceCropType::NoCrop, |primary| {
+            primary.config_face_crop_type
+        }),
+        face_crop_size: primary.map_or(0, |primary| primary.config_face_crop_size),
+        face_crop_padding: primary.map_or(0.0, |primary| primary.config_face_crop_padding),
+    }
+}